@@ -0,0 +1,500 @@
+//! Tamper-evident, append-only log of vault access.
+//!
+//! The log lives next to the vault (`vault.json.log`). Its first line holds a
+//! random log key encrypted under the vault key; every following line is one
+//! [`Record`], encrypted with a key derived from the log key and chained to
+//! the previous line with an HMAC. A small head file (`vault.json.log.head`)
+//! carries an HMAC over the last sequence number, so removing records from
+//! the end is detected as well as editing, reordering or dropping them.
+//! Writers serialize on a separate lock file (`vault.json.log.lock`), since
+//! rewriting the log or head replaces those files.
+//!
+//! Writing needs the vault key, so attempts that never unlock the vault
+//! (e.g. a wrong password) cannot be recorded.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::error::{io_context, OwnkeyError, Result};
+use crate::session;
+use crate::vault::UnlockedVault;
+use crate::vault_store;
+
+const VERSION: u32 = 1;
+
+/// One logged command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub seq: u64,
+    pub time: DateTime<Utc>,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub user: String,
+    pub host: String,
+    /// `ok`, or `error: ...` with the error message.
+    pub result: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    nonce: String,
+    key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    seq: u64,
+    nonce: String,
+    ciphertext: String,
+    mac: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Head {
+    seq: u64,
+    tag: String,
+}
+
+/// An open access log, holding the keys derived from its log key.
+pub struct AccessLog {
+    path: PathBuf,
+    log_key: [u8; 32],
+}
+
+impl AccessLog {
+    /// Open (or create) the log for the vault at `vault_path`. Returns `None`
+    /// for legacy plaintext vaults, which have no key to protect a log with.
+    pub fn open(vault_path: &str, unlocked: &UnlockedVault) -> Result<Option<Self>> {
+        match unlocked.key_bytes() {
+            Some(vault_key) => Self::open_with_key(vault_path, vault_key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Open the log with the vault key from the session cache, for commands
+    /// that do not unlock the vault themselves. `None` if there is no session.
+    pub fn open_from_session(vault_path: &str) -> Result<Option<Self>> {
+        match session::load(vault_path) {
+            Ok(Some(vault_key)) => Self::open_with_key(vault_path, &vault_key).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn open_with_key(vault_path: &str, vault_key: &[u8; 32]) -> Result<Self> {
+        let path = log_path(vault_path);
+        // Under the log lock, so two processes cannot both start a new log.
+        let log_key = vault_store::with_lock(&lock_path(&path), || {
+            let contents = read_if_exists(&path)?;
+            match contents.lines().next() {
+                Some(header) => unwrap_key(header, vault_key),
+                None => {
+                    let mut log_key = [0u8; 32];
+                    OsRng.fill_bytes(&mut log_key);
+                    append_line(&path, &wrap_key(&log_key, vault_key)?)?;
+                    Ok(log_key)
+                }
+            }
+        })?;
+        Ok(AccessLog { path, log_key })
+    }
+
+    /// Re-encrypt the log key under the (new) key of `unlocked`, e.g. after
+    /// the vault password was rotated. Records are left untouched.
+    pub fn rewrap(&self, unlocked: &UnlockedVault) -> Result<()> {
+        let Some(vault_key) = unlocked.key_bytes() else {
+            return Ok(());
+        };
+        let header = wrap_key(&self.log_key, vault_key)?;
+        vault_store::with_lock(&lock_path(&self.path), || {
+            let contents = read_if_exists(&self.path)?;
+            let records = contents.split_once('\n').map_or("", |(_, rest)| rest);
+            vault_store::atomic_write(&self.path, format!("{header}\n{records}"))
+        })
+    }
+
+    /// Append a record for `command` run on `key` by the current user.
+    ///
+    /// Reading the last record, appending the next one and updating the head
+    /// all happen under an exclusive lock on the log, so concurrent commands
+    /// never write the same sequence number.
+    pub fn append(&self, command: &str, key: Option<&str>, result: &str) -> Result<Record> {
+        vault_store::with_lock(&lock_path(&self.path), || self.append_locked(command, key, result))
+    }
+
+    fn append_locked(&self, command: &str, key: Option<&str>, result: &str) -> Result<Record> {
+        let contents = read_if_exists(&self.path)?;
+        let (seq, prev_mac) = match contents.lines().skip(1).last() {
+            Some(last) => {
+                let line = parse_line(last)?;
+                (line.seq, decode(&line.mac)?)
+            }
+            None => (0, vec![0u8; 32]),
+        };
+
+        let record = Record {
+            seq: seq + 1,
+            time: Utc::now(),
+            command: command.to_string(),
+            key: key.map(str::to_string),
+            user: current_user(),
+            host: current_host(),
+            result: result.to_string(),
+        };
+        let plaintext = serde_json::to_vec(&record)
+            .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize log record: {e}")))?;
+        let (nonce, ciphertext) = encrypt(&self.subkey(b"encrypt"), &plaintext)?;
+        let mac = self.chain_mac(&prev_mac, record.seq, &nonce, &ciphertext);
+        let line = Line {
+            seq: record.seq,
+            nonce: encode(&nonce),
+            ciphertext: encode(&ciphertext),
+            mac: encode(&mac),
+        };
+        let json = serde_json::to_string(&line)
+            .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize log record: {e}")))?;
+        append_line(&self.path, &json)?;
+
+        let head = Head {
+            seq: record.seq,
+            tag: encode(&self.head_tag(record.seq, &mac)),
+        };
+        let head = serde_json::to_string(&head)
+            .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize log head: {e}")))?;
+        vault_store::atomic_write(&head_path(&self.path), &head)?;
+        Ok(record)
+    }
+
+    /// Decrypt every record, checking the chain as it goes. Stops at the
+    /// first problem and returns it alongside the records read before it.
+    pub fn read(&self) -> Result<(Vec<Record>, Option<String>)> {
+        let contents = read_if_exists(&self.path)?;
+        let mut records = Vec::new();
+        let mut prev_mac = vec![0u8; 32];
+        for (index, text) in contents.lines().skip(1).enumerate() {
+            let expected = index as u64 + 1;
+            let line = match parse_line(text) {
+                Ok(line) => line,
+                Err(_) => return Ok((records, Some(format!("record {expected} is unreadable")))),
+            };
+            if line.seq != expected {
+                let problem = format!("expected record {expected} but found record {}", line.seq);
+                return Ok((records, Some(problem)));
+            }
+            let decoded = decode(&line.nonce).and_then(|n| Ok((n, decode(&line.ciphertext)?)));
+            let mac = decode(&line.mac);
+            let (Ok((nonce, ciphertext)), Ok(mac)) = (decoded, mac) else {
+                return Ok((records, Some(format!("record {expected} is unreadable"))));
+            };
+            if !self.verify_chain(&prev_mac, line.seq, &nonce, &ciphertext, &mac) {
+                return Ok((records, Some(format!("record {expected} was modified or removed"))));
+            }
+            let record = decrypt(&self.subkey(b"encrypt"), &nonce, &ciphertext)
+                .ok()
+                .and_then(|plaintext| serde_json::from_slice::<Record>(&plaintext).ok());
+            match record {
+                Some(record) if record.seq == line.seq => records.push(record),
+                _ => return Ok((records, Some(format!("record {expected} cannot be decrypted")))),
+            }
+            prev_mac = mac;
+        }
+
+        let last = records.len() as u64;
+        let head = fs::read_to_string(head_path(&self.path))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Head>(&text).ok());
+        let problem = match head {
+            None if last == 0 => None,
+            None => Some("the head record is missing".to_string()),
+            Some(head) if head.seq > last => Some(format!(
+                "the log ends at record {last} but {} were written; it was truncated",
+                head.seq
+            )),
+            Some(head)
+                if head.seq != last
+                    || decode(&head.tag).map_or(true, |tag| {
+                        self.head_mac(head.seq, &prev_mac).verify_slice(&tag).is_err()
+                    }) =>
+            {
+                Some("the head record does not match the log".to_string())
+            }
+            Some(_) => None,
+        };
+        Ok((records, problem))
+    }
+
+    /// Check the whole chain, returning the number of intact records.
+    pub fn verify(&self) -> Result<usize> {
+        match self.read()? {
+            (records, None) => Ok(records.len()),
+            (_, Some(problem)) => Err(OwnkeyError::Corrupt(format!(
+                "access log {} failed verification: {problem}",
+                self.path.display()
+            ))),
+        }
+    }
+
+    fn subkey(&self, purpose: &[u8]) -> [u8; 32] {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.log_key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(b"ownkey access log ");
+        mac.update(purpose);
+        mac.finalize().into_bytes().into()
+    }
+
+    fn chain(&self, prev_mac: &[u8], seq: u64, nonce: &[u8], ciphertext: &[u8]) -> Hmac<Sha256> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.subkey(b"chain"))
+            .expect("HMAC accepts keys of any length");
+        mac.update(prev_mac);
+        mac.update(&seq.to_be_bytes());
+        mac.update(nonce);
+        mac.update(ciphertext);
+        mac
+    }
+
+    fn chain_mac(&self, prev_mac: &[u8], seq: u64, nonce: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+        self.chain(prev_mac, seq, nonce, ciphertext)
+            .finalize()
+            .into_bytes()
+            .to_vec()
+    }
+
+    fn verify_chain(&self, prev_mac: &[u8], seq: u64, nonce: &[u8], ciphertext: &[u8], mac: &[u8]) -> bool {
+        self.chain(prev_mac, seq, nonce, ciphertext).verify_slice(mac).is_ok()
+    }
+
+    fn head_mac(&self, seq: u64, last_mac: &[u8]) -> Hmac<Sha256> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.subkey(b"head"))
+            .expect("HMAC accepts keys of any length");
+        mac.update(&seq.to_be_bytes());
+        mac.update(last_mac);
+        mac
+    }
+
+    fn head_tag(&self, seq: u64, last_mac: &[u8]) -> Vec<u8> {
+        self.head_mac(seq, last_mac).finalize().into_bytes().to_vec()
+    }
+}
+
+/// Where the log for the vault at `vault_path` is kept.
+pub fn log_path(vault_path: &str) -> PathBuf {
+    PathBuf::from(format!("{vault_path}.log"))
+}
+
+fn head_path(log_path: &Path) -> PathBuf {
+    sibling(log_path, ".head")
+}
+
+/// The file writers lock. It is never replaced, unlike the log and head,
+/// which `rewrap` and `append` write through a temp file and rename.
+fn lock_path(log_path: &Path) -> PathBuf {
+    sibling(log_path, ".lock")
+}
+
+fn sibling(log_path: &Path, suffix: &str) -> PathBuf {
+    let mut path = log_path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Append one line to the log file, creating it with 0600 permissions.
+fn append_line(path: &Path, line: &str) -> Result<()> {
+    let mut open = OpenOptions::new();
+    open.create(true).append(true);
+    #[cfg(unix)]
+    {
+        open.mode(0o600);
+    }
+    let mut file = open
+        .open(path)
+        .map_err(io_context(format!("failed to open {}", path.display())))?;
+    writeln!(file, "{line}").map_err(io_context("failed to write access log"))?;
+    file.sync_all().map_err(io_context("failed to sync access log"))
+}
+
+fn read_if_exists(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(io_context(format!("failed to read {}", path.display()))(err)),
+    }
+}
+
+fn wrap_key(log_key: &[u8; 32], vault_key: &[u8; 32]) -> Result<String> {
+    let (nonce, key) = encrypt(vault_key, log_key)?;
+    let header = Header {
+        version: VERSION,
+        nonce: encode(&nonce),
+        key: encode(&key),
+    };
+    serde_json::to_string(&header)
+        .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize log header: {e}")))
+}
+
+fn unwrap_key(header: &str, vault_key: &[u8; 32]) -> Result<[u8; 32]> {
+    let header: Header = serde_json::from_str(header)
+        .map_err(|_| OwnkeyError::Corrupt("access log header is damaged".into()))?;
+    if header.version != VERSION {
+        return Err(OwnkeyError::Corrupt(format!(
+            "unsupported access log version {}",
+            header.version
+        )));
+    }
+    decrypt(vault_key, &decode(&header.nonce)?, &decode(&header.key)?)
+        .ok()
+        .and_then(|key| <[u8; 32]>::try_from(key).ok())
+        .ok_or_else(|| {
+            OwnkeyError::Corrupt("access log key cannot be decrypted with this vault's key".into())
+        })
+}
+
+fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let cipher = Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| OwnkeyError::Crypto(format!("{e:?}")))?;
+    Ok((nonce.to_vec(), ciphertext))
+}
+
+fn decrypt(key: &[u8; 32], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if nonce.len() != 12 {
+        return Err(OwnkeyError::Corrupt("invalid nonce length".into()));
+    }
+    let cipher = Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| OwnkeyError::Corrupt("access log record cannot be decrypted".into()))
+}
+
+fn parse_line(text: &str) -> Result<Line> {
+    serde_json::from_str(text)
+        .map_err(|_| OwnkeyError::Corrupt("access log record is damaged".into()))
+}
+
+fn encode(bytes: &[u8]) -> String {
+    general_purpose::STANDARD.encode(bytes)
+}
+
+fn decode(text: &str) -> Result<Vec<u8>> {
+    general_purpose::STANDARD
+        .decode(text)
+        .map_err(|_| OwnkeyError::Corrupt("access log contains invalid base64".into()))
+}
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".into())
+}
+
+fn current_host() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "unknown".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Vault;
+    use crate::vault::VaultKey;
+
+    fn temp_log(name: &str) -> (String, AccessLog, UnlockedVault) {
+        let vault_path = std::env::temp_dir()
+            .join(format!("ownkey_log_{name}_{}.json", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        let unlocked = UnlockedVault::new(Vault::default(), VaultKey::generate("pw"));
+        let log = AccessLog::open(&vault_path, &unlocked).unwrap().unwrap();
+        (vault_path, log, unlocked)
+    }
+
+    #[test]
+    fn records_round_trip_and_survive_rekey() {
+        let (vault_path, log, mut unlocked) = temp_log("round_trip");
+        log.append("view", Some("db"), "ok").unwrap();
+        log.append("delete", Some("api"), "error: No entry found for key api").unwrap();
+
+        unlocked.rekey("new pw");
+        log.rewrap(&unlocked).unwrap();
+        let reopened = AccessLog::open(&vault_path, &unlocked).unwrap().unwrap();
+        let (records, problem) = reopened.read().unwrap();
+        assert_eq!(problem, None);
+        assert_eq!(records.len(), 2);
+        assert_eq!((records[0].seq, records[0].command.as_str()), (1, "view"));
+        assert_eq!(records[1].key.as_deref(), Some("api"));
+        assert_eq!(reopened.verify().unwrap(), 2);
+
+        let stale = UnlockedVault::new(Vault::default(), VaultKey::generate("pw"));
+        assert!(AccessLog::open(&vault_path, &stale).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rewrap_keeps_the_lock_file_that_appends_wait_on() {
+        use std::os::unix::fs::MetadataExt;
+
+        let (vault_path, log, unlocked) = temp_log("rewrap_lock");
+        log.append("view", Some("db"), "ok").unwrap();
+        let inode = |path: &Path| fs::metadata(path).unwrap().ino();
+        let (lock_before, log_before) = (inode(&lock_path(&log.path)), inode(&log.path));
+
+        log.rewrap(&unlocked).unwrap();
+        assert_ne!(inode(&log.path), log_before, "rewrap replaces the log file");
+        assert_eq!(inode(&lock_path(&log.path)), lock_before, "but not the lock file");
+
+        log.append("view", Some("db"), "ok").unwrap();
+        let reopened = AccessLog::open(&vault_path, &unlocked).unwrap().unwrap();
+        assert_eq!(reopened.verify().unwrap(), 2);
+    }
+
+    #[test]
+    fn detects_edits_removals_and_truncation() {
+        let (_, log, _) = temp_log("tamper");
+        for key in ["a", "b", "c"] {
+            log.append("view", Some(key), "ok").unwrap();
+        }
+        let original = fs::read_to_string(&log.path).unwrap();
+        let lines: Vec<&str> = original.lines().collect();
+
+        // Drop the middle record.
+        fs::write(&log.path, [lines[0], lines[1], lines[3]].join("\n")).unwrap();
+        assert!(log.verify().is_err());
+
+        // Drop the last record.
+        fs::write(&log.path, lines[..3].join("\n")).unwrap();
+        let (records, problem) = log.read().unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(problem.unwrap().contains("truncated"));
+
+        // Swap in another record's ciphertext.
+        let mut second: Line = serde_json::from_str(lines[2]).unwrap();
+        let first: Line = serde_json::from_str(lines[1]).unwrap();
+        second.ciphertext = first.ciphertext;
+        let edited = serde_json::to_string(&second).unwrap();
+        fs::write(&log.path, [lines[0], lines[1], &edited, lines[3]].join("\n")).unwrap();
+        assert!(log.verify().is_err());
+
+        fs::write(&log.path, original).unwrap();
+        assert_eq!(log.verify().unwrap(), 3);
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::btree_map;
+use std::path::Path;
+
+use crate::error::{OwnkeyError, Result};
+use crate::models;
+use crate::vault::{self, PasswordOptions, UnlockedVault, VaultKey};
+
+/// How to obtain the key for [`Vault::open`].
+pub enum Unlock {
+    /// Derive the key from the given master password.
+    Password(String),
+    /// Read the master password from the macOS Keychain.
+    Keychain { service: String, account: String },
+    /// Behave like the CLI: try the session cache, then the Keychain, then
+    /// prompt on the terminal.
+    Interactive,
+}
+
+/// An unlocked vault on disk.
+///
+/// Changes made with [`Vault::set`] and [`Vault::remove`] stay in memory
+/// until [`Vault::save`] re-encrypts and writes the file.
+///
+/// ```no_run
+/// use ownkey::{Unlock, Vault};
+///
+/// let mut vault = Vault::open("vault.json", Unlock::Password("pw".into()))?;
+/// vault.set("openai_key", "sk-...");
+/// vault.save()?;
+/// # Ok::<(), ownkey::OwnkeyError>(())
+/// ```
+pub struct Vault {
+    path: String,
+    no_session: bool,
+    unlocked: UnlockedVault,
+}
+
+impl Vault {
+    /// Open and decrypt the vault at `path`.
+    pub fn open(path: impl AsRef<Path>, unlock: Unlock) -> Result<Self> {
+        let path = path.as_ref().to_string_lossy().to_string();
+        if !Path::new(&path).exists() {
+            return Err(OwnkeyError::VaultNotFound(path));
+        }
+
+        let (password, keychain_service, keychain_account, no_session) = match &unlock {
+            Unlock::Password(p) => (Some(p.as_str()), "ownkey", None, true),
+            Unlock::Keychain { service, account } => {
+                (None, service.as_str(), Some(account.as_str()), true)
+            }
+            Unlock::Interactive => (None, "ownkey", None, false),
+        };
+        let opts = PasswordOptions {
+            password,
+            keychain_account,
+            keychain_service,
+            vault_path: &path,
+            no_session,
+        };
+        let mut unlocked = vault::load_vault_with_password(&path, &opts)?;
+        if let (Unlock::Password(p), false) = (&unlock, unlocked.has_key()) {
+            // Legacy plaintext vault: encrypt it under the given password on the next save.
+            unlocked.rekey(p);
+        }
+
+        Ok(Self {
+            path,
+            no_session,
+            unlocked,
+        })
+    }
+
+    /// Create a new, empty vault at `path` encrypted under `password`.
+    ///
+    /// Fails if a file already exists at `path`.
+    pub fn create(path: impl AsRef<Path>, password: &str) -> Result<Self> {
+        let path = path.as_ref().to_string_lossy().to_string();
+        if Path::new(&path).exists() {
+            return Err(OwnkeyError::AlreadyExists(path));
+        }
+
+        let mut vault = Self {
+            unlocked: UnlockedVault::new(models::Vault::default(), VaultKey::generate(password)),
+            no_session: true,
+            path,
+        };
+        vault.save()?;
+        Ok(vault)
+    }
+
+    /// Path of the vault file this handle reads and writes.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Return the value stored under `key`.
+    pub fn get(&self, key: &str) -> Result<&str> {
+        self.unlocked
+            .vault
+            .entries
+            .get(key)
+            .map(|entry| entry.value.as_str())
+            .ok_or_else(|| OwnkeyError::NotFound(key.to_string()))
+    }
+
+    /// Store `value` under `key`, returning the previous value if there was one.
+    /// Tags on an existing entry are kept.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        match self.unlocked.vault.entries.entry(key.into()) {
+            btree_map::Entry::Occupied(mut slot) => {
+                let previous = slot.get().value.clone();
+                slot.get_mut().rotate(value);
+                Some(previous)
+            }
+            btree_map::Entry::Vacant(slot) => {
+                slot.insert(models::Entry::created(value));
+                None
+            }
+        }
+    }
+
+    /// Remove `key` and return its value.
+    pub fn remove(&mut self, key: &str) -> Result<String> {
+        self.unlocked
+            .vault
+            .entries
+            .remove(key)
+            .map(|entry| entry.value)
+            .ok_or_else(|| OwnkeyError::NotFound(key.to_string()))
+    }
+
+    /// All key names, sorted.
+    pub fn list(&self) -> Vec<&str> {
+        self.unlocked.vault.entries.keys().map(String::as_str).collect()
+    }
+
+    /// Key names inside a `/`-separated namespace such as `prod/`, sorted.
+    pub fn list_namespace(&self, namespace: &str) -> Vec<&str> {
+        self.unlocked.vault.keys_in(namespace).map(String::as_str).collect()
+    }
+
+    /// Re-encrypt the vault with the key it was unlocked with and write it to disk.
+    pub fn save(&mut self) -> Result<()> {
+        let opts = PasswordOptions {
+            no_session: self.no_session,
+            ..PasswordOptions::new(None, &self.path)
+        };
+        vault::save_vault_with_password(&self.path, &mut self.unlocked, &opts)
+    }
+}
@@ -0,0 +1,176 @@
+//! File attachments: TLS keys, kubeconfigs, service-account JSON and other
+//! files, binary or not.
+//!
+//! Each file is encrypted with AES-256-GCM under its own random key and
+//! written as a blob to `<vault>.attachments/<id>`. The vault only keeps the
+//! [`Attachment`] metadata, including that key, so loading the vault never
+//! reads or decrypts the blobs, and changing the vault password needs no
+//! re-encryption. A blob is `nonce || ciphertext`, with the blob id as
+//! associated data so blobs cannot be swapped between entries.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::error::{io_context, OwnkeyError, Result};
+use crate::models::Attachment;
+use crate::vault_store;
+
+/// Largest file that can be attached, 10 MiB.
+pub const MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+const NONCE_LEN: usize = 12;
+
+/// Directory holding the attachment blobs of the vault at `vault_path`.
+pub fn blob_dir(vault_path: &str) -> PathBuf {
+    PathBuf::from(format!("{vault_path}.attachments"))
+}
+
+/// Encrypt `contents` into a new blob and return its metadata.
+pub fn store(vault_path: &str, name: &str, contents: &[u8]) -> Result<Attachment> {
+    check_size(name, contents.len() as u64)?;
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let id = uuid::Uuid::new_v4().to_string();
+
+    let ciphertext = cipher(&key)
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: contents, aad: id.as_bytes() })
+        .map_err(|e| OwnkeyError::Crypto(format!("{e:?}")))?;
+
+    let dir = blob_dir(vault_path);
+    create_dir(&dir)?;
+    let mut blob = nonce.to_vec();
+    blob.extend_from_slice(&ciphertext);
+    vault_store::atomic_write(&dir.join(&id), &blob)?;
+
+    Ok(Attachment {
+        id,
+        name: name.to_string(),
+        size: contents.len() as u64,
+        sha256: sha256_hex(contents),
+        key: general_purpose::STANDARD.encode(key),
+    })
+}
+
+/// Read and decrypt the blob behind `attachment`.
+pub fn read(vault_path: &str, attachment: &Attachment) -> Result<Vec<u8>> {
+    let path = blob_dir(vault_path).join(&attachment.id);
+    let blob = match fs::read(&path) {
+        Ok(blob) => blob,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return Err(OwnkeyError::Corrupt(format!(
+                "attachment blob {} is missing",
+                path.display()
+            )))
+        }
+        Err(err) => return Err(io_context(format!("failed to read {}", path.display()))(err)),
+    };
+    let damaged = || OwnkeyError::Corrupt(format!("attachment blob {} is damaged", path.display()));
+
+    let key = general_purpose::STANDARD
+        .decode(&attachment.key)
+        .ok()
+        .and_then(|key| <[u8; 32]>::try_from(key).ok())
+        .ok_or_else(damaged)?;
+    if blob.len() < NONCE_LEN {
+        return Err(damaged());
+    }
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    let contents = cipher(&key)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload { msg: ciphertext, aad: attachment.id.as_bytes() },
+        )
+        .map_err(|_| damaged())?;
+    if sha256_hex(&contents) != attachment.sha256 {
+        return Err(damaged());
+    }
+    Ok(contents)
+}
+
+/// Delete the blob behind `attachment`; a blob that is already gone is fine.
+pub fn remove(vault_path: &str, attachment: &Attachment) -> Result<()> {
+    let path = blob_dir(vault_path).join(&attachment.id);
+    match fs::remove_file(&path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            Err(io_context(format!("failed to remove {}", path.display()))(err))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fail with `InvalidInput` if a file of `size` bytes is over [`MAX_SIZE`].
+pub fn check_size(name: &str, size: u64) -> Result<()> {
+    if size > MAX_SIZE {
+        return Err(OwnkeyError::InvalidInput(format!(
+            "{name} is {size} bytes; attachments are limited to {} MiB",
+            MAX_SIZE / 1024 / 1024
+        )));
+    }
+    Ok(())
+}
+
+fn cipher(key: &[u8; 32]) -> Aes256Gcm {
+    Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(key))
+}
+
+fn create_dir(dir: &PathBuf) -> Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(dir)
+        .map_err(io_context(format!("failed to create {}", dir.display())))
+}
+
+fn sha256_hex(contents: &[u8]) -> String {
+    Sha256::digest(contents).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_vault() -> String {
+        let path = std::env::temp_dir().join(format!("ownkey_attach_{}.json", uuid::Uuid::new_v4()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn round_trips_binary_contents() {
+        let vault = temp_vault();
+        let contents: Vec<u8> = (0..=255u8).cycle().take(5000).collect();
+        let attachment = store(&vault, "blob.bin", &contents).unwrap();
+        assert_eq!(attachment.size, 5000);
+
+        let blob = fs::read(blob_dir(&vault).join(&attachment.id)).unwrap();
+        assert!(!blob.windows(64).any(|w| w == &contents[..64]), "blob must be encrypted");
+        assert_eq!(read(&vault, &attachment).unwrap(), contents);
+
+        let other = store(&vault, "other.bin", b"other").unwrap();
+        let swapped = Attachment { id: other.id.clone(), ..attachment.clone() };
+        assert!(matches!(read(&vault, &swapped), Err(OwnkeyError::Corrupt(_))));
+
+        remove(&vault, &attachment).unwrap();
+        remove(&vault, &attachment).unwrap();
+        assert!(matches!(read(&vault, &attachment), Err(OwnkeyError::Corrupt(_))));
+    }
+
+    #[test]
+    fn rejects_oversized_files() {
+        assert!(check_size("big.iso", MAX_SIZE).is_ok());
+        assert!(matches!(check_size("big.iso", MAX_SIZE + 1), Err(OwnkeyError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,213 @@
+//! Offline lookups in a downloaded Pwned Passwords (HIBP) SHA-1 hash list.
+//!
+//! Two layouts are supported, both as produced by the official downloader:
+//! a single file of `HASH:COUNT` lines ordered by hash, or a directory of
+//! per-prefix files (`ABCDE.txt`) holding `SUFFIX:COUNT` lines, the same
+//! shape the k-anonymity range API returns. Lookups binary-search the sorted
+//! lines with a handful of seeks, so multi-gigabyte lists are never read
+//! into memory, and nothing leaves the machine.
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use sha1::{Digest, Sha1};
+
+use crate::error::{io_context, Result};
+
+/// Length of the hash prefix used to name per-prefix files.
+const PREFIX_LEN: usize = 5;
+
+pub enum BreachDb {
+    /// One file with full hashes, ordered by hash.
+    File { file: File, len: u64 },
+    /// A directory of `PREFIX.txt` files with hash suffixes.
+    Directory(PathBuf),
+}
+
+impl BreachDb {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let context = || format!("failed to open breach database {}", path.display());
+        if path.is_dir() {
+            return Ok(BreachDb::Directory(path.to_path_buf()));
+        }
+        let file = File::open(path).map_err(io_context(context()))?;
+        let len = file.metadata().map_err(io_context(context()))?.len();
+        Ok(BreachDb::File { file, len })
+    }
+
+    /// How often `secret` appears in the list, or `None` if it does not.
+    pub fn count(&mut self, secret: &str) -> Result<Option<u64>> {
+        let hash = sha1_hex(secret);
+        match self {
+            BreachDb::File { file, len } => search(file, *len, &hash),
+            BreachDb::Directory(dir) => {
+                let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+                let path = dir.join(format!("{prefix}.txt"));
+                let mut file = match File::open(&path) {
+                    Ok(file) => file,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                    Err(err) => {
+                        return Err(io_context(format!("failed to open {}", path.display()))(err))
+                    }
+                };
+                let len = file
+                    .metadata()
+                    .map_err(io_context(format!("failed to read {}", path.display())))?
+                    .len();
+                search(&mut file, len, suffix)
+            }
+        }
+    }
+}
+
+/// Uppercase hex SHA-1, the form used by Pwned Passwords.
+pub fn sha1_hex(secret: &str) -> String {
+    Sha1::digest(secret.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect()
+}
+
+/// Binary search the sorted `HASH:COUNT` lines of `file` for `hash`.
+///
+/// The search works on byte offsets: probe the middle of `lo..hi`, move to
+/// the first line starting there, and narrow the range by comparing it.
+/// Lines that are not `HASH:COUNT` (blank lines, headers) are stepped over,
+/// and padding entries with a count of 0 do not count as found.
+fn search(file: &mut File, len: u64, hash: &str) -> Result<Option<u64>> {
+    let (mut lo, mut hi) = (0, len);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let Some((start, line, candidate, count)) = parsed_line_from(file, mid, hi)? else {
+            hi = mid;
+            continue;
+        };
+        match candidate.as_str().cmp(hash) {
+            Ordering::Equal => return Ok(Some(count).filter(|count| *count > 0)),
+            Ordering::Less => lo = start + line.len() as u64,
+            Ordering::Greater => hi = mid,
+        }
+    }
+    Ok(None)
+}
+
+/// The first parsable line starting at or after `pos` and before `end`, with
+/// its offset, raw text and parsed hash and count.
+fn parsed_line_from(file: &mut File, mut pos: u64, end: u64) -> Result<Option<(u64, String, String, u64)>> {
+    loop {
+        let probe = line_from(file, pos).map_err(io_context("failed to read breach database"))?;
+        let Some((start, line)) = probe.filter(|(start, _)| *start < end) else {
+            return Ok(None);
+        };
+        if let Some((candidate, count)) = parse_line(&line) {
+            return Ok(Some((start, line, candidate, count)));
+        }
+        pos = start + line.len() as u64;
+    }
+}
+
+/// The first line starting at or after `pos`, with its offset. The line
+/// keeps its terminator so its length is the distance to the next line.
+fn line_from(file: &mut File, pos: u64) -> io::Result<Option<(u64, String)>> {
+    let mut start = pos;
+    let mut reader = if pos == 0 {
+        file.seek(SeekFrom::Start(0))?;
+        BufReader::with_capacity(256, &*file)
+    } else {
+        // Step back one byte: if it is a newline a line starts right at `pos`,
+        // otherwise this skips the rest of the line `pos` falls in.
+        file.seek(SeekFrom::Start(pos - 1))?;
+        let mut reader = BufReader::with_capacity(256, &*file);
+        let mut partial = Vec::new();
+        reader.read_until(b'\n', &mut partial)?;
+        start = pos - 1 + partial.len() as u64;
+        reader
+    };
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    Ok((!line.is_empty()).then_some((start, line)))
+}
+
+/// Split a `HASH:COUNT` line; `None` for anything else.
+fn parse_line(line: &str) -> Option<(String, u64)> {
+    let (hash, count) = line.trim_end().split_once(':')?;
+    let count = count.trim().parse().ok()?;
+    Some((hash.trim().to_ascii_uppercase(), count))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ownkey_breach_{name}_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn finds_hashes_in_a_sorted_file() {
+        let mut passwords: Vec<(String, u64)> = (0..500)
+            .map(|i| (sha1_hex(&format!("password{i}")), i + 1))
+            .collect();
+        passwords.sort();
+        let lines: String = passwords
+            .iter()
+            .map(|(hash, count)| format!("{hash}:{count}\r\n"))
+            .collect();
+        let path = temp_dir("file").join("pwned.txt");
+        fs::write(&path, lines).unwrap();
+
+        let mut db = BreachDb::open(&path).unwrap();
+        assert_eq!(sha1_hex("password"), "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8");
+        for i in [0, 1, 250, 498, 499] {
+            assert_eq!(db.count(&format!("password{i}")).unwrap(), Some(i + 1));
+        }
+        assert_eq!(db.count("password500").unwrap(), None);
+        assert_eq!(db.count("").unwrap(), None);
+    }
+
+    #[test]
+    fn finds_suffixes_in_prefix_directory() {
+        let dir = temp_dir("dir");
+        fs::write(
+            dir.join("5BAA6.txt"),
+            "003D68EB55068C33ACE09247EE4C639306B:3\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\n",
+        )
+        .unwrap();
+
+        let mut db = BreachDb::open(&dir).unwrap();
+        assert_eq!(db.count("password").unwrap(), Some(9545824));
+        assert_eq!(db.count("not in the list").unwrap(), None);
+    }
+
+    #[test]
+    fn skips_malformed_lines_and_padding() {
+        let mut passwords: Vec<(String, u64)> = (0..200)
+            .map(|i| (sha1_hex(&format!("password{i}")), if i % 10 == 0 { 0 } else { i }))
+            .collect();
+        passwords.sort();
+        let mut lines = String::from("hash:count\r\n\r\n");
+        for (n, (hash, count)) in passwords.iter().enumerate() {
+            lines.push_str(&format!("{hash}:{count}\r\n"));
+            if n % 7 == 0 {
+                lines.push_str(if n % 2 == 0 { "\r\n" } else { "\n" });
+            }
+        }
+        lines.push_str("not a hash line");
+        let path = temp_dir("malformed").join("pwned.txt");
+        fs::write(&path, lines).unwrap();
+
+        let mut db = BreachDb::open(&path).unwrap();
+        for i in 0..200 {
+            let expected = if i % 10 == 0 { None } else { Some(i) };
+            assert_eq!(db.count(&format!("password{i}")).unwrap(), expected, "password{i}");
+        }
+        assert_eq!(db.count("password200").unwrap(), None);
+    }
+}
@@ -1,4 +1,9 @@
-use clap::{Parser, Subcommand};
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use ownkey::models::EntryKind;
+use ownkey::totp::Algorithm;
 
 pub mod sync_cmd;
 
@@ -9,19 +14,64 @@ pub mod sync_cmd;
     long_about = "ownkey is a small, learning-focused CLI password manager.\n\
 It stores key/value secrets in an encrypted vault on disk and is designed to showcase\n\
 practical Rust patterns for CLI apps, encryption, and error handling.",
-    after_long_help = "Examples:\n  ownkey init                         # Create a new encrypted vault (~/.ownkey/vault.json)\n  ownkey init ./vault.json --password testpw\n  ownkey add gj_key 123123            # Add a secret\n  ownkey list                         # List stored keys\n  ownkey view gj_key                  # View a secret by key\n  ownkey delete gj_key --yes          # Delete a key without interactive prompt\n  ownkey search gj                    # Search keys by keyword\n"
+    after_long_help = "Examples:\n  ownkey init                         # Create a new encrypted vault (~/.ownkey/vault.json)\n  ownkey init ./vault.json --password testpw\n  ownkey add gj_key 123123            # Add a secret\n  ownkey add db_password --generate --length 32\n  ownkey add tls_key --from-file ./server.pem   # Multiline value from a file\n  ownkey update gj_key                # Change an existing key (shows what changes first)\n  ownkey attach kubeconfig ~/.kube/config   # Store a file as an encrypted attachment\n  ownkey add db/prod --kind database --field host=db.internal --field user=app --generate\n  ownkey view db/prod --field host\n  ownkey extract kubeconfig -o ./config\n  pbpaste | ownkey update gj_key --stdin --yes\n  ownkey generate --format passphrase --words 7\n  ownkey list                         # List stored keys\n  ownkey list prod/ --tree            # Show the prod/ namespace as a tree\n  ownkey mv prod/ archive/prod/       # Move a whole namespace\n  ownkey view gj_key                  # View a secret by key\n  ownkey copy                         # Pick an entry with the fuzzy finder, then copy it\n  ownkey copy gj_key --clear-after 10s\n  ownkey add github_2fa 'otpauth://totp/GitHub?secret=...' --kind totp\n  ownkey totp github_2fa --copy       # Current 2FA code to the clipboard\n  ownkey delete gj_key --yes          # Delete a key without interactive prompt\n  ownkey history gj_key               # Earlier values kept after overwrites\n  ownkey view gj_key --version 2\n  ownkey rollback gj_key --to 2\n  ownkey search gj                    # Search keys by keyword\n  ownkey search --fuzzy strk          # Ranked fuzzy search over key names\n  ownkey add stripe_key --tag payments --tag prod\n  ownkey list --tag payments          # Filter by tag\n  ownkey policy stripe_key --expires 2025-12-31 --rotate-every 90d\n  ownkey audit --within 30d           # Expired, expiring, unrotated and duplicate entries\n  ownkey audit --strength --json      # Also flag weak, placeholder and malformed values\n  ownkey audit --breach-db ./pwned-passwords-sha1.txt\n  ownkey log --key stripe_key --since 2025-01-01\n  ownkey log verify                   # Check the access log chain for edits or truncation\n  ownkey run -e OPENAI_API_KEY=openai_key -- python app.py\n  ownkey vault create work            # A second, separate vault\n  ownkey -v work list                 # Use it for one command\n  ownkey vault use work               # Or make it the default\n  echo work > .ownkey                 # Or pick it inside this project directory\n  OWNKEY_PASSWORD_FILE=~/.vault-pw ownkey list --no-session\n  ownkey config show                  # Which vault, password source and settings apply, and why\n  eval \"$(ownkey env --prefix prod/)\"\n  ownkey env --prefix prod/ --strip-prefix --var-prefix APP_   # prod/db_url as APP_DB_URL\n\n\
+Exit codes:\n  0  success\n  1  general failure (I/O, clipboard, ...)\n  2  invalid usage or input\n  3  key not found\n  4  wrong password\n  5  vault locked by another process\n  6  vault file corrupt\n  7  permission denied\n  8  sync failed\n  9  vault or backup file missing\n  10 vault already exists\n"
 )]
 pub struct Cli {
+    /// How to report errors on stderr (exit codes are the same for both formats)
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+    #[command(flatten)]
+    pub global: GlobalArgs,
     #[command(subcommand)]
     pub command: Commands,
 }
 
+/// Options accepted by every command, before or after the subcommand name.
+/// Unset ones fall back to the environment, then to config.toml (see
+/// `ownkey config show`).
+#[derive(clap::Args, Debug, Default)]
+#[command(next_help_heading = "Global options")]
+pub struct GlobalArgs {
+    /// Use this named vault (see `ownkey vault list`) instead of the default [env: OWNKEY_VAULT]
+    #[arg(short = 'v', long = "vault", global = true, value_name = "NAME")]
+    pub vault: Option<String>,
+    /// Path to the vault file, instead of the named or default vault
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub path: Option<String>,
+    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
+    #[arg(long, global = true, conflicts_with = "password_file")]
+    pub password: Option<String>,
+    /// Read the vault password from the first line of this file [env: OWNKEY_PASSWORD_FILE]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub password_file: Option<String>,
+    /// Keychain account holding the vault password (macOS only) [env: OWNKEY_KEYCHAIN_ACCOUNT]
+    #[arg(long, global = true)]
+    pub keychain_account: Option<String>,
+    /// Keychain service name (macOS only) [default: ownkey]
+    #[arg(long, global = true)]
+    pub keychain_service: Option<String>,
+    /// Disable session cache usage for this command [env: OWNKEY_NO_SESSION=1]
+    #[arg(long, global = true)]
+    pub no_session: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// `Error: <message>`
+    Text,
+    /// A single JSON object with `kind`, `exit_code` and `message`
+    Json,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Initialize a new encrypted vault
     Init(InitArgs),
     /// Add a new key/value secret to the vault
     Add(AddArgs),
+    /// Change the value of an existing key after showing what changes
+    Update(UpdateArgs),
     /// List all keys stored in the vault
     List(ListArgs),
     /// View a secret by key
@@ -30,14 +80,57 @@ pub enum Commands {
     Copy(CopyArgs),
     /// Delete a secret by key
     Delete(DeleteArgs),
-    /// Search secrets by keyword in key or value
+    /// Store a file (TLS key, kubeconfig, ...) in the vault as an encrypted attachment
+    Attach(AttachArgs),
+    /// Write a stored attachment back out to a file
+    Extract(ExtractArgs),
+    /// Add, remove or list entry tags
+    #[command(subcommand)]
+    Tag(TagCommand),
+    /// Print the current TOTP code for a stored 2FA seed
+    Totp(TotpArgs),
+    /// Generate a random password, passphrase or token
+    Generate(GenerateArgs),
+    /// List the stored versions of an entry's value
+    History(HistoryArgs),
+    /// Restore an earlier version of an entry's value
+    Rollback(RollbackArgs),
+    /// Show or change an entry's expiry date and rotation policy
+    Policy(PolicyArgs),
+    /// Report expired, soon-to-expire, never-rotated, duplicate, weak and breached entries
+    Audit(AuditArgs),
+    /// Rename a key, or move every key under a namespace to another one
+    #[command(visible_alias = "rename")]
+    Mv(MvArgs),
+    /// Search key names (and optionally values) by substring, exact, regex or fuzzy match
     Search(SearchArgs),
+    /// Run a command with secrets injected into its environment
+    Run(RunArgs),
+    /// Print shell export statements for secrets (use with `eval "$(ownkey env ...)"`)
+    Env(EnvArgs),
+    /// Fill `{{ ownkey "key" }}` placeholders in a template with vault secrets
+    Render(RenderArgs),
+    /// Bulk-import secrets from .env, JSON, CSV or another password manager
+    Import(ImportArgs),
+    /// Export secrets as plaintext or as a separately encrypted vault file
+    Export(ExportArgs),
+    /// Show or verify the encrypted access log of vault reads and changes
+    Log(LogArgs),
+    /// Create, list and choose named vaults
+    #[command(subcommand)]
+    Vault(VaultCommand),
+    /// Show the settings in effect and where each one came from
+    #[command(subcommand)]
+    Config(ConfigCommand),
     /// Rotate the vault encryption password
     #[command(name = "rotate-password")]
     RotatePassword(RotatePasswordArgs),
     /// Restore the encrypted backup over the current vault (hidden)
     #[command(name = "restore-backup", hide = true)]
     RestoreBackup,
+    /// Background process started by `copy` that owns and later clears the clipboard (hidden)
+    #[command(name = "clipboard-helper", hide = true)]
+    ClipboardHelper(ClipboardHelperArgs),
     /// Sync vault (placeholder)
     Sync(SyncArgs),
     /// Login to sync backend (placeholder)
@@ -48,180 +141,605 @@ pub enum Commands {
 
 #[derive(clap::Args, Debug)]
 pub struct InitArgs {
-    /// Path to the vault file (defaults to ~/.ownkey/vault.json)
-    pub path: Option<String>,
-    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
-    #[arg(long)]
-    pub password: Option<String>,
-    /// Optional keychain account name (macOS only); when provided, password will be saved to Keychain
-    #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
-    /// Disable session cache usage for this command
-    #[arg(long)]
-    pub no_session: bool,
+    /// Path to the vault file (same as --path; defaults to ~/.ownkey/vault.json)
+    #[arg(value_name = "PATH", conflicts_with = "path")]
+    pub vault_path: Option<String>,
 }
 
 #[derive(clap::Args, Debug, Default)]
 pub struct AddArgs {
-    /// Optional path to the vault file
-    #[arg(short, long)]
-    pub path: Option<String>,
     /// Key name to store
     pub key: String,
-    /// Value to store
-    pub value: Option<String>,
+    #[command(flatten)]
+    pub source: ValueSource,
+    /// Overwrite the value if the key already exists
+    #[arg(short, long)]
+    pub force: bool,
+    /// Entry kind: `secret` (default), `totp` (otpauth:// URI or base32 seed),
+    /// `login`, `api_key`, `database`, `ssh_key` or `note`
+    #[arg(long)]
+    pub kind: Option<EntryKind>,
+    /// Set a field of a typed entry, e.g. `--field username=me` (repeatable; the
+    /// primary field, such as a login's password, can also be the value)
+    #[arg(long = "field", value_name = "NAME=VALUE", value_parser = parse_field)]
+    pub fields: Vec<(String, String)>,
     /// Optional notes (unused for KV mode, kept for future)
     #[arg(long)]
     pub notes: Option<String>,
-    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
-    #[arg(long)]
-    pub password: Option<String>,
-    /// Optional keychain account name (macOS only)
-    #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
-    /// Disable session cache usage for this command
-    #[arg(long)]
-    pub no_session: bool,
+    /// Tag the entry (repeatable, e.g. `--tag payments --tag prod`)
+    #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+    /// Date the secret stops working, e.g. `2025-12-31` (UTC) or an RFC 3339 timestamp
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub expires: Option<DateTime<Utc>>,
+    /// How often the value should be rotated, e.g. `90d` or `12w`
+    #[arg(long, value_name = "DURATION", value_parser = ownkey::config::parse_duration)]
+    pub rotate_every: Option<Duration>,
 }
 
+/// Where `add` and `update` take the new value from; without any of these
+/// it is prompted for twice. The generator options only apply with
+/// `--generate`.
 #[derive(clap::Args, Debug, Default)]
-pub struct ListArgs {
-    /// Optional path to the vault file
+#[command(group(
+    ArgGroup::new("generator")
+        .args(GENERATOR_ARGS)
+        .multiple(true)
+        .requires("generate")
+        .conflicts_with_all(["value", "from_file", "stdin"])
+))]
+pub struct ValueSource {
+    /// Value to store
+    pub value: Option<String>,
+    /// Generate the value instead of reading it (see `ownkey generate` for the options)
+    #[arg(long, conflicts_with_all = ["value", "from_file", "stdin"])]
+    pub generate: bool,
+    #[command(flatten)]
+    pub generator: GenerateOptions,
+    /// Read the value from a file, kept byte for byte (e.g. a PEM key)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["value", "stdin"])]
+    pub from_file: Option<String>,
+    /// Read the value from standard input; one trailing newline is dropped
+    #[arg(long, conflicts_with = "value")]
+    pub stdin: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct UpdateArgs {
+    /// Existing key to change
+    pub key: String,
+    #[command(flatten)]
+    pub source: ValueSource,
+    /// Change a field of a typed entry, e.g. `--field host=db2` (repeatable; an
+    /// empty value clears an optional field)
+    #[arg(long = "field", value_name = "NAME=VALUE", value_parser = parse_field)]
+    pub fields: Vec<(String, String)>,
+    /// Skip the confirmation prompt (required with --stdin)
     #[arg(short, long)]
-    pub path: Option<String>,
-    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
-    #[arg(long)]
-    pub password: Option<String>,
-    /// Optional keychain account name (macOS only)
-    #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
-    /// Disable session cache usage for this command
-    #[arg(long)]
-    pub no_session: bool,
+    pub yes: bool,
 }
 
 #[derive(clap::Args, Debug)]
-pub struct ViewArgs {
-    /// Optional path to the vault file
+pub struct AttachArgs {
+    /// Key name to store the file under
+    pub key: String,
+    /// File to attach (at most 10 MiB)
+    pub file: String,
+    /// Replace the file if the key already holds one
     #[arg(short, long)]
-    pub path: Option<String>,
-    /// Key to view
+    pub force: bool,
+    /// Tag the entry (repeatable)
+    #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExtractArgs {
+    /// Key holding the attachment
     pub key: String,
-    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
-    #[arg(long)]
-    pub password: Option<String>,
-    /// Optional keychain account name (macOS only)
+    /// File to write (created with 0600 permissions); omit to write to stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<String>,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct ListArgs {
+    /// Only list keys inside this namespace, e.g. `prod/` or `prod/db`
+    pub namespace: Option<String>,
+    /// Render keys as a folder tree instead of one key per line
     #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
-    /// Disable session cache usage for this command
+    pub tree: bool,
+    /// Only list entries carrying this tag (repeatable; all must match)
+    #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ViewArgs {
+    /// Key to view (omit to pick one interactively)
+    pub key: Option<String>,
+    /// Choose the entry with the interactive fuzzy finder; a given key becomes the initial query
     #[arg(long)]
-    pub no_session: bool,
-    /// Output as JSON with key and value
+    pub pick: bool,
+    /// Show an earlier value by its version number (see `ownkey history`)
+    #[arg(long, value_name = "N")]
+    pub version: Option<u32>,
+    /// Show one field of a typed entry, e.g. `--field password` or `--field host`
+    #[arg(long, value_name = "NAME", conflicts_with = "version")]
+    pub field: Option<String>,
+    /// Output as JSON with key, kind, value and the kind's fields
     #[arg(long)]
     pub json: bool,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct CopyArgs {
-    /// Optional path to the vault file
-    #[arg(short, long)]
-    pub path: Option<String>,
-    /// Key to copy
-    pub key: String,
-    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
-    #[arg(long)]
-    pub password: Option<String>,
-    /// Optional keychain account name (macOS only)
+    /// Key to copy (omit to pick one interactively)
+    pub key: Option<String>,
+    /// Choose the entry with the interactive fuzzy finder; a given key becomes the initial query
     #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
-    /// Disable session cache usage for this command
+    pub pick: bool,
+    /// Clear the clipboard after this long if it still holds the secret, e.g. `30s` or `2m`
+    /// (`0` keeps it; defaults to `clipboard_clear_after` in config.toml)
+    #[arg(long, value_name = "DURATION", value_parser = ownkey::config::parse_duration)]
+    pub clear_after: Option<Duration>,
+    /// Also copy to the primary selection (middle-click paste; X11 and Wayland only)
     #[arg(long)]
-    pub no_session: bool,
+    pub primary: bool,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct DeleteArgs {
-    /// Optional path to the vault file
-    #[arg(short, long)]
-    pub path: Option<String>,
     /// Key to delete
     pub key: String,
-    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
-    #[arg(long)]
-    pub password: Option<String>,
-    /// Optional keychain account name (macOS only)
-    #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
     /// Skip interactive confirmation and delete immediately
     #[arg(short = 'y', long = "yes")]
     pub yes: bool,
-    /// Disable session cache usage for this command
-    #[arg(long)]
-    pub no_session: bool,
 }
 
 #[derive(clap::Args, Debug)]
-pub struct SearchArgs {
-    /// Optional path to the vault file
+pub struct MvArgs {
+    /// Key to rename, or a namespace ending in `/` to move a whole subtree
+    pub from: String,
+    /// New key name, or a namespace ending in `/` to move the key into under
+    /// its own name (also the destination when moving a subtree)
+    pub to: String,
+    /// Overwrite existing keys at the destination
     #[arg(short, long)]
-    pub path: Option<String>,
+    pub force: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SearchArgs {
     /// Keyword to search for
     pub keyword: String,
-    /// Optional password (falls back to interactive prompt; using --password may leak in shell history)
-    #[arg(long)]
-    pub password: Option<String>,
-    /// Optional keychain account name (macOS only)
-    #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
-    /// Disable session cache usage for this command
+    /// Only search entries carrying this tag (repeatable; all must match)
+    #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+    /// Match whole fields exactly (case-sensitive)
+    #[arg(long, conflicts_with_all = ["regex", "fuzzy"])]
+    pub exact: bool,
+    /// Treat the keyword as a regular expression
+    #[arg(long, conflicts_with = "fuzzy")]
+    pub regex: bool,
+    /// Rank entries by fuzzy subsequence match, best first
     #[arg(long)]
-    pub no_session: bool,
-    /// Reserved for future behavior (currently has no effect)
+    pub fuzzy: bool,
+    /// Also search secret values (off by default so searches do not reveal value contents)
     #[arg(long)]
-    pub exact: bool,
+    pub values: bool,
+    /// Only search key names (the default; kept for explicitness)
+    #[arg(long, conflicts_with = "values")]
+    pub keys_only: bool,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct RotatePasswordArgs {
-    /// Optional path to the vault file
-    #[arg(short, long)]
-    pub path: Option<String>,
-    /// Optional current password (falls back to interactive prompt)
-    #[arg(long)]
-    pub password: Option<String>,
     /// New password to set (if omitted, will be prompted for interactively)
     #[arg(long = "new-password")]
     pub new_password: Option<String>,
-    /// Optional keychain account name (macOS only)
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    /// Environment variable to set from a vault entry, as NAME=KEY (repeatable)
+    #[arg(short, long = "env", value_name = "NAME=KEY")]
+    pub env: Vec<String>,
+    /// File with one NAME=KEY mapping per line (blank lines and `#` comments are ignored)
+    #[arg(long, value_name = "FILE")]
+    pub env_file: Option<String>,
+    /// Command to run, followed by its arguments (after `--`)
+    #[arg(last = true, required = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct EnvArgs {
+    /// Keys to export
+    pub keys: Vec<String>,
+    /// Export every entry whose vault key starts with this prefix (selects keys only;
+    /// see --strip-prefix and --var-prefix for variable names)
     #[arg(long)]
-    pub keychain_account: Option<String>,
-    /// Keychain service name (macOS only)
-    #[arg(long, default_value = "ownkey")]
-    pub keychain_service: String,
-    /// Disable session cache usage for this command
+    pub prefix: Option<String>,
+    /// Export entries carrying this tag (repeatable; all must match, combines with --prefix)
+    #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+    /// Leave the --prefix out of variable names (`prod/db_url` becomes `DB_URL`)
+    #[arg(long, requires = "prefix")]
+    pub strip_prefix: bool,
+    /// Prepend this to every variable name, e.g. `PROJECT_`
+    #[arg(long, value_name = "PREFIX")]
+    pub var_prefix: Option<String>,
+    /// Output syntax
+    #[arg(long, value_enum, default_value_t = EnvFormat::Bash)]
+    pub format: EnvFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvFormat {
+    Bash,
+    Zsh,
+    Fish,
+    Dotenv,
+    Powershell,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RenderArgs {
+    /// Template file to render (reads stdin when omitted or `-`)
+    pub input: Option<String>,
+    /// Write the rendered output to this file with 0600 permissions (defaults to stdout)
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// List the keys referenced by the template without unlocking the vault
     #[arg(long)]
-    pub no_session: bool,
+    pub check: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// File to import
+    pub file: String,
+    /// Format of the file being imported
+    #[arg(long, value_enum)]
+    pub format: ImportFormat,
+    /// What to do when a key already exists
+    #[arg(long, value_enum, default_value_t = OnConflict::Skip)]
+    pub on_conflict: OnConflict,
+    /// Show what would be imported without changing the vault
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// KEY=VALUE lines
+    Dotenv,
+    /// A flat JSON object of key/value strings
+    Json,
+    /// CSV with `key`/`name` and `value` columns (or the first two columns)
+    Csv,
+    /// Bitwarden unencrypted JSON export
+    BitwardenJson,
+    /// 1Password CSV export
+    #[value(name = "1password-csv")]
+    OnePasswordCsv,
+    /// KeePass 2 XML export
+    KeepassXml,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnConflict {
+    /// Keep the existing value
+    Skip,
+    /// Replace the existing value
+    Overwrite,
+    /// Import under `<key>_2`, `<key>_3`, ...
+    Rename,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    /// Output format
+    #[arg(long, value_enum)]
+    pub format: ExportFormat,
+    /// Write to this file with 0600 permissions (defaults to stdout)
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Only export entries whose key starts with this prefix
+    #[arg(long)]
+    pub prefix: Option<String>,
+    /// Only export entries carrying this tag (repeatable; all must match)
+    #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+    /// Confirm that secrets may be written unencrypted (required for json, dotenv and csv)
+    #[arg(long)]
+    pub plaintext: bool,
+    /// Passphrase for `ownkey-encrypted` exports (falls back to interactive prompt)
+    #[arg(long)]
+    pub export_password: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A flat JSON object of key/value strings (plaintext)
+    Json,
+    /// KEY="VALUE" lines, with keys mapped like `ownkey env` (plaintext)
+    Dotenv,
+    /// `key,value` CSV with a header row (plaintext)
+    Csv,
+    /// A vault file encrypted under a separate export passphrase
+    OwnkeyEncrypted,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum VaultCommand {
+    /// Register a named vault in config.toml and initialize it
+    Create(VaultCreateArgs),
+    /// List the named vaults, marking the default and the one in use
+    List(VaultListArgs),
+    /// Make a named vault the default
+    Use(VaultUseArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct VaultCreateArgs {
+    /// Name for the vault (letters, digits, `-` and `_`)
+    #[arg(value_parser = parse_vault_name)]
+    pub name: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Show the effective vault, password source, keychain and session settings
+    Show(ConfigShowArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ConfigShowArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct VaultListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct VaultUseArgs {
+    /// Name of a registered vault
+    pub name: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagCommand {
+    /// Attach tags to an entry
+    Add(TagEditArgs),
+    /// Remove tags from an entry
+    Remove(TagEditArgs),
+    /// List the tags of one entry, or every tag in the vault with its entry count
+    List(TagListArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TagEditArgs {
+    /// Key of the entry to change
+    pub key: String,
+    /// Tags to add or remove
+    #[arg(required = true, value_parser = parse_tag)]
+    pub tags: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TagListArgs {
+    /// Only show the tags of this entry
+    pub key: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TotpArgs {
+    /// Key of the TOTP entry
+    pub key: String,
+    /// Copy the code to the clipboard instead of printing it
+    #[arg(long)]
+    pub copy: bool,
+    /// Override the number of digits
+    #[arg(long)]
+    pub digits: Option<u32>,
+    /// Override the time step in seconds
+    #[arg(long)]
+    pub period: Option<u64>,
+    /// Override the HMAC algorithm (SHA1, SHA256 or SHA512)
+    #[arg(long)]
+    pub algorithm: Option<Algorithm>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GenerateArgs {
+    #[command(flatten)]
+    pub options: GenerateOptions,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct GenerateOptions {
+    /// What to generate
+    #[arg(long, value_enum, default_value_t = GenerateFormat::Password)]
+    pub format: GenerateFormat,
+    /// Characters for passwords (default 24), or random bytes for hex/base64 (default 32)
+    #[arg(short, long)]
+    pub length: Option<usize>,
+    /// Number of words in a passphrase
+    #[arg(long, default_value_t = 6)]
+    pub words: usize,
+    /// Separator between passphrase words
+    #[arg(long, default_value = "-")]
+    pub separator: String,
+    /// Leave out lowercase letters
+    #[arg(long)]
+    pub no_lower: bool,
+    /// Leave out uppercase letters
+    #[arg(long)]
+    pub no_upper: bool,
+    /// Leave out digits
+    #[arg(long)]
+    pub no_digits: bool,
+    /// Leave out symbols
+    #[arg(long)]
+    pub no_symbols: bool,
+    /// Leave out look-alike characters such as 0/O/o and 1/l/I/|
+    #[arg(long)]
+    pub exclude_ambiguous: bool,
+}
+
+/// The argument ids of [`GenerateOptions`], which `add` and `update` only
+/// accept together with `--generate`.
+const GENERATOR_ARGS: [&str; 9] = [
+    "format",
+    "length",
+    "words",
+    "separator",
+    "no_lower",
+    "no_upper",
+    "no_digits",
+    "no_symbols",
+    "exclude_ambiguous",
+];
+
+impl ValueSource {
+    /// Whether any way of giving a value was used; otherwise it is prompted for.
+    pub fn is_given(&self) -> bool {
+        self.value.is_some() || self.generate || self.from_file.is_some() || self.stdin
+    }
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            format: GenerateFormat::Password,
+            length: None,
+            words: 6,
+            separator: "-".into(),
+            no_lower: false,
+            no_upper: false,
+            no_digits: false,
+            no_symbols: false,
+            exclude_ambiguous: false,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GenerateFormat {
+    /// Random characters from the selected classes
+    Password,
+    /// Diceware-style words from the bundled 2048-word list
+    Passphrase,
+    /// Random bytes as lowercase hex
+    Hex,
+    /// Random bytes as standard base64
+    Base64,
+    /// A random (version 4) UUID
+    Uuid,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct HistoryArgs {
+    /// Key of the entry
+    pub key: String,
+    /// Output versions as JSON (numbers and dates only, never values)
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RollbackArgs {
+    /// Key of the entry
+    pub key: String,
+    /// Version to restore; the current value is kept in the history
+    #[arg(long, value_name = "N")]
+    pub to: u32,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PolicyArgs {
+    /// Key of the entry to show or change
+    pub key: String,
+    /// Date the secret stops working, e.g. `2025-12-31` (UTC) or an RFC 3339 timestamp
+    #[arg(long, value_name = "DATE", value_parser = parse_date, conflicts_with = "no_expiry")]
+    pub expires: Option<DateTime<Utc>>,
+    /// Remove the expiry date
+    #[arg(long)]
+    pub no_expiry: bool,
+    /// How often the value should be rotated, e.g. `90d` or `12w`
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = ownkey::config::parse_duration,
+        conflicts_with = "no_rotation"
+    )]
+    pub rotate_every: Option<Duration>,
+    /// Remove the rotation policy
+    #[arg(long)]
+    pub no_rotation: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AuditArgs {
+    /// Report entries expiring within this long as expiring soon
+    #[arg(long, value_name = "DURATION", default_value = "30d", value_parser = ownkey::config::parse_duration)]
+    pub within: Duration,
+    /// Also check values for low entropy, placeholders and malformed AWS, GitHub or Slack credentials
+    #[arg(long)]
+    pub strength: bool,
+    /// Also look every value up in a downloaded Pwned Passwords SHA-1 list (ordered by hash),
+    /// either one file or a directory of per-prefix files; nothing is sent over the network
+    #[arg(long, value_name = "PATH")]
+    pub breach_db: Option<String>,
+    /// Output the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LogArgs {
+    /// `show` (default) lists records; `verify` checks the hash chain for edits and truncation
+    #[arg(value_enum, default_value_t = LogAction::Show)]
+    pub action: LogAction,
+    /// Only records for this key
+    #[arg(long)]
+    pub key: Option<String>,
+    /// Only records of this command, e.g. `view` or `delete`
+    #[arg(long)]
+    pub command: Option<String>,
+    /// Only records from this date on, e.g. `2025-01-01` (UTC) or an RFC 3339 timestamp
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only the most recent N matching records
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+    /// Output records as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogAction {
+    Show,
+    Verify,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ClipboardHelperArgs {
+    /// Seconds until the clipboard is cleared; 0 keeps it
+    #[arg(long)]
+    pub clear_after: u64,
+    /// Also set the primary selection
+    #[arg(long)]
+    pub primary: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -233,3 +751,30 @@ pub struct LoginArgs {
     #[arg(long)]
     pub username: Option<String>,
 }
+
+fn parse_tag(tag: &str) -> Result<String, String> {
+    ownkey::models::validate_tag(tag)?;
+    Ok(tag.to_string())
+}
+
+fn parse_vault_name(name: &str) -> Result<String, String> {
+    ownkey::config::validate_vault_name(name)?;
+    Ok(name.to_string())
+}
+
+fn parse_field(input: &str) -> Result<(String, String), String> {
+    let (name, value) = input
+        .split_once('=')
+        .ok_or_else(|| format!("invalid field '{input}': expected NAME=VALUE"))?;
+    Ok((name.trim().to_string(), value.to_string()))
+}
+
+/// Accept a plain date (midnight UTC) or a full RFC 3339 timestamp.
+fn parse_date(input: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(date.and_time(Default::default()).and_utc());
+    }
+    DateTime::parse_from_rfc3339(input)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| format!("invalid date '{input}': expected YYYY-MM-DD or an RFC 3339 timestamp"))
+}
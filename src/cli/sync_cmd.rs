@@ -1,25 +1,37 @@
-use crate::config::{self, Config, SyncProvider};
-use crate::sync::backend::SyncBackend;
-use crate::sync::noop::NoopSyncBackend;
-use crate::sync::file::FileSyncBackend;
+use std::path::PathBuf;
 
-pub fn handle_login(username: Option<&str>) {
+use ownkey::config::{self, Config, SyncProvider};
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::sync::backend::SyncBackend;
+use ownkey::sync::file::FileSyncBackend;
+use ownkey::sync::noop::NoopSyncBackend;
+use crate::commands::log;
+use crate::options::Options;
+
+pub fn handle_login(username: Option<&str>) -> Result<()> {
     let backend = select_backend();
-    let _ = backend.login(username);
+    backend.login(username)?;
     println!("Sync login not implemented yet");
+    Ok(())
 }
 
-pub fn handle_logout() {
+pub fn handle_logout() -> Result<()> {
     let backend = select_backend();
-    let _ = backend.logout();
+    backend.logout()?;
     println!("Sync logout not implemented yet");
+    Ok(())
 }
 
-pub fn handle_sync() {
+pub fn handle_sync(options: &Options) -> Result<()> {
     let backend = select_backend();
-    let _ = backend.pull();
-    let _ = backend.push(&[]);
+    let synced = backend
+        .pull()
+        .and_then(|_| backend.push(&[]))
+        .map_err(OwnkeyError::from);
+    log::record_from_session(options.vault_path(), "sync", None, &synced);
+    synced?;
     println!("Sync not implemented yet");
+    Ok(())
 }
 
 fn select_backend() -> Box<dyn SyncBackend> {
@@ -28,9 +40,18 @@ fn select_backend() -> Box<dyn SyncBackend> {
         Config::default()
     });
 
-    match cfg.sync_provider {
+    let settings = config::selected().map(|selected| &selected.settings);
+    let provider = settings
+        .and_then(|settings| settings.sync_provider)
+        .unwrap_or(cfg.sync_provider);
+    let target = settings.and_then(|settings| {
+        let remote = settings.sync_path.as_ref()?;
+        Some(FileSyncBackend::new(PathBuf::from(&settings.path), PathBuf::from(remote)))
+    });
+
+    match provider {
         SyncProvider::LocalOnly => Box::new(NoopSyncBackend),
-        SyncProvider::File => match FileSyncBackend::new_default() {
+        SyncProvider::File => match target.map_or_else(FileSyncBackend::new_default, Ok) {
             Ok(backend) => Box::new(backend),
             Err(err) => {
                 eprintln!(
@@ -1,48 +1,137 @@
-use anyhow::{anyhow, Result};
-use rpassword::prompt_password;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
 
-use crate::cli::AddArgs;
-use crate::vault::{ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password, warn_if_insecure_cli_password, PasswordOptions};
-use crate::vault_store;
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::models::{Entry, EntryKind};
+use ownkey::vault::{
+    ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
+};
+use rpassword::prompt_password;
 
-pub fn handle(args: AddArgs) -> Result<()> {
-    let path = if let Some(ref path) = args.path {
-        path.clone()
-    } else {
-        vault_store::default_vault_path()?
-    };
+use crate::cli::{AddArgs, ValueSource};
+use crate::commands::{attach, generate, log};
+use crate::options::Options;
 
-    let opts = PasswordOptions {
-        password: args.password.as_deref(),
-        keychain_account: args.keychain_account.as_deref(),
-        keychain_service: &args.keychain_service,
-        vault_path: &path,
-        no_session: args.no_session,
-    };
+pub fn handle(args: AddArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
 
-    warn_if_insecure_cli_password(&opts);
+    let opts = options.password_options(path)?;
 
     let name = args.key;
-    let secret = match args.value {
-        Some(v) => v,
-        None => {
-            let first = prompt_password("Value: ").map_err(|_| anyhow!("failed to read value"))?;
-            let second =
-                prompt_password("Confirm: ").map_err(|_| anyhow!("failed to read value"))?;
-            if first != second {
-                return Err(anyhow!("Values do not match. Aborting."));
-            }
-            first
+    let _notes = args.notes;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+    if let Some(existing) = unlocked.vault.entries.get(&name) {
+        if !args.force {
+            return Err(OwnkeyError::InvalidInput(format!(
+                "key '{name}' already exists; use `ownkey update {name}` or `add --force` to overwrite it"
+            )));
         }
+        attach::ensure_not_file(&name, existing)?;
+    }
+    if args.kind == Some(EntryKind::File) {
+        return Err(OwnkeyError::InvalidInput(
+            "use `ownkey attach KEY FILE` to store files".into(),
+        ));
+    }
+    let kind = args
+        .kind
+        .or_else(|| unlocked.vault.entries.get(&name).map(|e| e.kind))
+        .unwrap_or_default();
+    let (primary, fields) = split_fields(kind, args.fields, &args.source)?;
+    let secret = match primary {
+        Some(value) => value,
+        None => read_value(args.source)?,
     };
-    let _notes = args.notes;
 
-    ensure_vault_exists_with_password(&path, &opts)?;
-    let mut vault = load_vault_with_password(&path, &opts)?;
-    vault.entries.insert(name.clone(), secret);
+    let entry = unlocked
+        .vault
+        .entries
+        .entry(name.clone())
+        .or_insert_with(|| Entry::created(secret.clone()));
+    entry.rotate(secret);
+    // Fields of another kind would not validate; the new kind starts afresh.
+    if entry.kind != kind {
+        entry.fields.clear();
+    }
+    entry.kind = kind;
+    entry.fields.extend(fields);
+    entry.fields.retain(|_, value| !value.is_empty());
+    entry.validate()?;
+    entry.tags.extend(args.tags);
+    if args.expires.is_some() {
+        entry.expires_at = args.expires;
+    }
+    if args.rotate_every.is_some() {
+        entry.rotate_every = args.rotate_every;
+    }
     // TODO: notes are currently unused in storage; consider persisting later.
-    save_vault_with_password(&path, &vault, &opts)?;
+    let saved = save_vault_with_password(path, &mut unlocked, &opts);
+    log::record(path, &unlocked, "add", Some(&name), &saved);
+    saved?;
 
     println!("{}", name);
     Ok(())
 }
+
+/// Split `--field` arguments into the value of the kind's primary field, if
+/// given that way, and the other fields. Giving the primary field as well as
+/// a value is an error.
+pub fn split_fields(
+    kind: EntryKind,
+    fields: Vec<(String, String)>,
+    source: &ValueSource,
+) -> Result<(Option<String>, BTreeMap<String, String>)> {
+    let mut primary = None;
+    let mut rest = BTreeMap::new();
+    for (name, value) in fields {
+        if kind.primary_field() == Some(name.as_str()) {
+            if source.is_given() {
+                return Err(OwnkeyError::InvalidInput(format!(
+                    "the {name} field is the entry's value; give it either as --field or as the value, not both"
+                )));
+            }
+            primary = Some(value);
+        } else {
+            rest.insert(name, value);
+        }
+    }
+    Ok((primary, rest))
+}
+
+/// The new value for `add` or `update`: given on the command line, generated,
+/// read from a file or stdin, or prompted for twice.
+pub fn read_value(source: ValueSource) -> Result<String> {
+    if let Some(value) = source.value {
+        return Ok(value);
+    }
+    if source.generate {
+        let (secret, bits) = generate::generate(&source.generator)?;
+        eprintln!("Generated a value with ~{bits:.0} bits of entropy.");
+        return Ok(secret);
+    }
+    if let Some(file) = source.from_file {
+        return fs::read_to_string(&file).map_err(io_context(format!("failed to read {file}")));
+    }
+    if source.stdin {
+        let mut value = String::new();
+        io::stdin()
+            .read_to_string(&mut value)
+            .map_err(io_context("failed to read value from stdin"))?;
+        if value.ends_with('\n') {
+            value.pop();
+            if value.ends_with('\r') {
+                value.pop();
+            }
+        }
+        return Ok(value);
+    }
+    let first = prompt_password("Value: ").map_err(io_context("failed to read value"))?;
+    let second = prompt_password("Confirm: ").map_err(io_context("failed to read value"))?;
+    if first != second {
+        return Err(OwnkeyError::InvalidInput("Values do not match. Aborting.".into()));
+    }
+    Ok(first)
+}
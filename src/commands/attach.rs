@@ -0,0 +1,134 @@
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+
+use chrono::Utc;
+use ownkey::attachment;
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::models::{Entry, EntryKind};
+use ownkey::vault::{
+    ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
+};
+use ownkey::vault_store;
+
+use crate::cli::{AttachArgs, ExtractArgs};
+use crate::commands::log;
+use crate::options::Options;
+
+pub fn handle(args: AttachArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    let file = Path::new(&args.file);
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| OwnkeyError::InvalidInput(format!("{} is not a file", args.file)))?;
+    let size = fs::metadata(file)
+        .map_err(io_context(format!("failed to read {}", args.file)))?
+        .len();
+    attachment::check_size(&name, size)?;
+    let contents = fs::read(file).map_err(io_context(format!("failed to read {}", args.file)))?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+    let previous = match unlocked.vault.entries.get(&args.key) {
+        None => None,
+        Some(entry) if entry.kind != EntryKind::File => {
+            return Err(OwnkeyError::InvalidInput(format!(
+                "key '{}' already holds a {} value; delete it first or pick another key",
+                args.key, entry.kind
+            )))
+        }
+        Some(entry) if !args.force => {
+            return Err(OwnkeyError::InvalidInput(format!(
+                "key '{}' already holds {}; pass --force to replace it",
+                args.key,
+                entry.attachment.as_ref().map_or("a file", |a| a.name.as_str())
+            )))
+        }
+        Some(entry) => entry.attachment.clone(),
+    };
+
+    let stored = attachment::store(path, &name, &contents)?;
+    let entry = unlocked.vault.entries.entry(args.key.clone()).or_insert_with(|| Entry {
+        kind: EntryKind::File,
+        ..Entry::created("")
+    });
+    if previous.is_some() {
+        entry.rotated_at = Some(Utc::now());
+    }
+    entry.attachment = Some(stored.clone());
+    entry.tags.extend(args.tags);
+    let saved = save_vault_with_password(path, &mut unlocked, &opts);
+    log::record(path, &unlocked, "attach", Some(&args.key), &saved);
+    // Whichever blob the vault no longer points to is dropped.
+    let unused = if saved.is_ok() { previous } else { Some(stored) };
+    if let Some(unused) = unused {
+        if let Err(err) = attachment::remove(path, &unused) {
+            eprintln!("Warning: {err}");
+        }
+    }
+    saved?;
+
+    println!("Attached {name} ({size} bytes) as {}", args.key);
+    Ok(())
+}
+
+pub fn extract(args: ExtractArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let unlocked = load_vault_with_password(path, &opts)?;
+    let contents = unlocked
+        .vault
+        .entries
+        .get(&args.key)
+        .ok_or_else(|| OwnkeyError::NotFound(args.key.clone()))
+        .and_then(|entry| {
+            entry.attachment.as_ref().ok_or_else(|| {
+                OwnkeyError::InvalidInput(format!(
+                    "key '{}' has no attachment; use `ownkey view {}`",
+                    args.key, args.key
+                ))
+            })
+        })
+        .and_then(|stored| attachment::read(path, stored));
+    log::record(path, &unlocked, "extract", Some(&args.key), &contents);
+    let contents = contents?;
+
+    match args.output {
+        Some(ref out) => {
+            vault_store::atomic_write(Path::new(out), &contents)?;
+            eprintln!("Wrote {} bytes to {out}", contents.len());
+        }
+        None => {
+            let mut stdout = io::stdout();
+            if stdout.is_terminal() && std::str::from_utf8(&contents).is_err() {
+                return Err(OwnkeyError::InvalidInput(
+                    "refusing to print binary data to a terminal; pass -o FILE or redirect stdout"
+                        .into(),
+                ));
+            }
+            stdout
+                .write_all(&contents)
+                .map_err(io_context("failed to write attachment"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Fail for entries that hold a file, which have no text value to show or
+/// change; points at `ownkey extract` / `ownkey attach` instead.
+pub fn ensure_not_file(key: &str, entry: &Entry) -> Result<()> {
+    if entry.kind != EntryKind::File {
+        return Ok(());
+    }
+    let name = entry.attachment.as_ref().map_or("a file", |a| a.name.as_str());
+    Err(OwnkeyError::InvalidInput(format!(
+        "key '{key}' holds {name}; use `ownkey extract {key}` or `ownkey attach {key} FILE --force`"
+    )))
+}
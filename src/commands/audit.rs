@@ -0,0 +1,82 @@
+use chrono::Utc;
+use ownkey::audit::{self, Dated, Report};
+use ownkey::breach::BreachDb;
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
+
+use crate::cli::AuditArgs;
+use crate::options::Options;
+
+pub fn handle(args: AuditArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    let within = chrono::Duration::from_std(args.within)
+        .map_err(|_| OwnkeyError::InvalidInput("--within is too large".into()))?;
+    let mut report = audit::check(&vault, Utc::now(), within);
+    if args.strength {
+        report.weak = Some(audit::strength(&vault));
+    }
+    if let Some(ref db) = args.breach_db {
+        let mut db = BreachDb::open(db)?;
+        report.breached = Some(audit::breaches(&vault, &mut db)?);
+    }
+    if args.json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| OwnkeyError::InvalidInput(format!("failed to encode report: {e}")))?;
+        println!("{json}");
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn print_report(report: &Report) {
+    print_dated("Expired", &report.expired);
+    print_dated("Expiring soon", &report.expiring_soon);
+    print_dated("Rotation due", &report.rotation_due);
+    if !report.duplicates.is_empty() {
+        println!("Duplicate values ({}):", report.duplicates.len());
+        for keys in &report.duplicates {
+            println!("  {}", keys.join(", "));
+        }
+    }
+    if let Some(weak) = report.weak.as_ref().filter(|weak| !weak.is_empty()) {
+        println!("Weak values ({}):", weak.len());
+        for weakness in weak {
+            println!("  {}  {}", weakness.key, weakness.issue);
+        }
+    }
+    if let Some(breached) = report.breached.as_ref().filter(|b| !b.is_empty()) {
+        println!("Found in breach list ({}):", breached.len());
+        for found in breached {
+            println!("  {}  seen {} times", found.key, found.count);
+        }
+    }
+    if !report.never_rotated.is_empty() {
+        println!("Never rotated ({}):", report.never_rotated.len());
+        for key in &report.never_rotated {
+            println!("  {key}");
+        }
+    }
+    if !report.has_problems() {
+        println!("No expired, expiring, overdue, duplicate, weak or breached entries.");
+    }
+}
+
+fn print_dated(title: &str, entries: &[Dated]) {
+    if entries.is_empty() {
+        return;
+    }
+    println!("{title} ({}):", entries.len());
+    for dated in entries {
+        match dated.date {
+            Some(date) => println!("  {}  {}", dated.key, date.format("%Y-%m-%d")),
+            None => println!("  {}  (no timestamps recorded)", dated.key),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use ownkey::config;
+use ownkey::error::Result;
+use serde_json::{json, Value};
+
+use crate::cli::{ConfigCommand, ConfigShowArgs};
+use crate::options::{Options, Password, Setting, Source};
+
+pub fn handle(command: ConfigCommand, options: &Options) -> Result<()> {
+    match command {
+        ConfigCommand::Show(args) => show(args, options),
+    }
+}
+
+fn show(args: ConfigShowArgs, options: &Options) -> Result<()> {
+    let config_file = config::config_path()?;
+    let rows = rows(options);
+
+    if args.json {
+        let mut object = serde_json::Map::new();
+        object.insert("config_file".into(), json!(config_file.display().to_string()));
+        for (name, value, source) in rows {
+            object.insert(
+                name.into(),
+                json!({ "value": value, "source": source.map(|source| source.to_string()) }),
+            );
+        }
+        println!("{}", Value::Object(object));
+        return Ok(());
+    }
+
+    println!("config file: {}", config_file.display());
+    let text = |value: &Value| match value {
+        Value::String(text) => text.clone(),
+        Value::Null => "-".to_string(),
+        other => other.to_string(),
+    };
+    let width = rows.iter().map(|(_, value, _)| text(value).len()).max().unwrap_or(0);
+    for (name, value, source) in &rows {
+        let source = match source {
+            Some(source) => source.to_string(),
+            None if *name == "password" => "not set; keychain or prompt".to_string(),
+            None => "not set".to_string(),
+        };
+        println!("{name:<16}  {:<width$}  ({source})", text(value));
+    }
+    Ok(())
+}
+
+/// Each option with its effective value (`null` when unset; never the
+/// password itself) and where that value came from.
+fn rows(options: &Options) -> Vec<(&'static str, Value, Option<Source>)> {
+    fn row<T>(setting: Option<&Setting<T>>, value: impl Fn(&T) -> Value) -> (Value, Option<Source>) {
+        setting.map_or((Value::Null, None), |setting| (value(&setting.value), Some(setting.source.clone())))
+    }
+    let text = |value: &String| json!(value);
+    let password = |password: &Password| match password {
+        Password::Literal(_) => json!("(hidden)"),
+        Password::File(file) => json!(file),
+    };
+
+    let (vault, vault_source) = row(options.vault.as_ref(), text);
+    let (path, path_source) = row(Some(&options.path), text);
+    let (password, password_source) = row(options.password.as_ref(), password);
+    let (account, account_source) = row(options.keychain_account.as_ref(), text);
+    let (service, service_source) = row(Some(&options.keychain_service), text);
+    let (no_session, no_session_source) = row(Some(&options.no_session), |value| json!(value));
+    vec![
+        ("vault", vault, vault_source),
+        ("path", path, path_source),
+        ("password", password, password_source),
+        ("keychain_account", account, account_source),
+        ("keychain_service", service, service_source),
+        ("no_session", no_session, no_session_source),
+    ]
+}
@@ -1,44 +1,255 @@
-use anyhow::{anyhow, Result};
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
 use arboard::Clipboard;
+use ownkey::config::Config;
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
 
-use crate::cli::CopyArgs;
-use crate::vault::{
-    ensure_vault_exists_with_password, load_vault_with_password, warn_if_insecure_cli_password,
-    PasswordOptions,
-};
-use crate::vault_store;
+use crate::cli::{ClipboardHelperArgs, CopyArgs};
+use crate::commands::{log, pick, view};
+use crate::options::Options;
 
-pub fn handle(args: CopyArgs) -> Result<()> {
-    let path = if let Some(ref path) = args.path {
-        path.clone()
-    } else {
-        vault_store::default_vault_path()?
+pub fn handle(args: CopyArgs, options: &Options, cfg: &Config) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let unlocked = load_vault_with_password(path, &opts)?;
+    let vault = &unlocked.vault;
+
+    let key = match args.key {
+        Some(key) if !args.pick => key,
+        query => pick::pick(vault, query.as_deref().unwrap_or(""))?,
     };
+    let clear_after = args.clear_after.unwrap_or(cfg.clipboard_clear_after);
+    let copied = view::lookup(vault, &key).and_then(|value| {
+        view::warn_if_stale(&key, &vault.entries[&key]);
+        to_clipboard(value, clear_after, args.primary)
+    });
+    log::record(path, &unlocked, "copy", Some(&key), &copied);
+    copied?;
+
+    if clear_after.is_zero() {
+        println!("Value for key '{}' copied to clipboard.", key);
+    } else {
+        println!(
+            "Value for key '{}' copied to clipboard; clearing in {}s.",
+            key,
+            clear_after.as_secs()
+        );
+    }
+    Ok(())
+}
+
+/// Start a detached `ownkey clipboard-helper` that owns the clipboard
+/// contents and clears them later. The secret goes over its stdin, never the
+/// command line or environment, and we wait for it to report whether setting
+/// the clipboard worked so errors still reach the user.
+pub fn to_clipboard(secret: &str, clear_after: Duration, primary: bool) -> Result<()> {
+    let exe = env::current_exe().map_err(io_context("failed to locate the ownkey executable"))?;
+    let mut command = Command::new(exe);
+    command
+        .arg("clipboard-helper")
+        .arg("--clear-after")
+        .arg(clear_after.as_secs().to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if primary {
+        command.arg("--primary");
+    }
+    detach(&mut command);
+
+    let mut child = command
+        .spawn()
+        .map_err(io_context("failed to start clipboard helper"))?;
+    {
+        let mut stdin = child.stdin.take().expect("helper stdin is piped");
+        stdin
+            .write_all(secret.as_bytes())
+            .map_err(io_context("failed to pass secret to clipboard helper"))?;
+    }
+
+    let mut status = String::new();
+    let stdout = child.stdout.take().expect("helper stdout is piped");
+    BufReader::new(stdout)
+        .read_line(&mut status)
+        .map_err(io_context("failed to read clipboard helper status"))?;
+    match status.trim_end() {
+        "ok" => Ok(()),
+        "" => Err(OwnkeyError::Clipboard("clipboard helper exited unexpectedly".into())),
+        message => Err(OwnkeyError::Clipboard(
+            message.strip_prefix("error: ").unwrap_or(message).to_string(),
+        )),
+    }
+}
+
+/// Keep the helper alive after the terminal or its process group goes away.
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+}
+
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detach(_command: &mut Command) {}
+
+/// Entry point of the hidden `clipboard-helper` subcommand.
+///
+/// On X11 and Wayland the process that set the clipboard has to stay around
+/// to serve it, so the helper keeps running until the timeout (or, with no
+/// timeout, until something else is copied). It then clears each selection
+/// only if it still holds our secret, leaving anything copied since alone.
+pub fn helper(args: ClipboardHelperArgs) -> Result<()> {
+    let mut secret = String::new();
+    io::stdin()
+        .read_to_string(&mut secret)
+        .map_err(io_context("failed to read secret"))?;
 
-    let opts = PasswordOptions {
-        password: args.password.as_deref(),
-        keychain_account: args.keychain_account.as_deref(),
-        keychain_service: &args.keychain_service,
-        vault_path: &path,
-        no_session: args.no_session,
+    let mut clipboard = match set_clipboard(&secret, args.primary) {
+        Ok(clipboard) => {
+            report("ok");
+            clipboard
+        }
+        Err(message) => {
+            report(&format!("error: {message}"));
+            return Ok(());
+        }
     };
 
-    warn_if_insecure_cli_password(&opts);
+    if args.clear_after == 0 {
+        hold_until_replaced(&mut clipboard, &secret);
+        return Ok(());
+    }
 
-    ensure_vault_exists_with_password(&path, &opts)?;
-    let vault = load_vault_with_password(&path, &opts)?;
+    let deadline = Instant::now() + Duration::from_secs(args.clear_after);
+    hold_until(&mut clipboard, &secret, deadline);
+    clear_if_unchanged(&mut clipboard, &secret, args.primary);
+    Ok(())
+}
 
-    let value = vault
-        .entries
-        .get(&args.key)
-        .ok_or_else(|| anyhow!("No entry found for key {}", args.key))?;
+fn report(line: &str) {
+    let mut stdout = io::stdout();
+    let _ = writeln!(stdout, "{line}");
+    let _ = stdout.flush();
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn set_clipboard(secret: &str, primary: bool) -> std::result::Result<Clipboard, String> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
 
     let mut clipboard =
-        Clipboard::new().map_err(|e| anyhow!("failed to access clipboard: {e}"))?;
+        Clipboard::new().map_err(|e| format!("failed to access clipboard: {e}"))?;
+    if primary {
+        clipboard
+            .set()
+            .clipboard(LinuxClipboardKind::Primary)
+            .exclude_from_history()
+            .text(secret)
+            .map_err(|e| format!("failed to copy to primary selection: {e}"))?;
+    }
     clipboard
-        .set_text(value.to_string())
-        .map_err(|e| anyhow!("failed to copy to clipboard: {e}"))?;
+        .set()
+        .exclude_from_history()
+        .text(secret)
+        .map_err(|e| format!("failed to copy to clipboard: {e}"))?;
+    Ok(clipboard)
+}
 
-    println!("Value for key '{}' copied to clipboard.", args.key);
-    Ok(())
+#[cfg(target_os = "macos")]
+fn set_clipboard(secret: &str, _primary: bool) -> std::result::Result<Clipboard, String> {
+    use arboard::SetExtApple;
+
+    let mut clipboard =
+        Clipboard::new().map_err(|e| format!("failed to access clipboard: {e}"))?;
+    clipboard
+        .set()
+        .exclude_from_history()
+        .text(secret)
+        .map_err(|e| format!("failed to copy to clipboard: {e}"))?;
+    Ok(clipboard)
+}
+
+#[cfg(windows)]
+fn set_clipboard(secret: &str, _primary: bool) -> std::result::Result<Clipboard, String> {
+    use arboard::SetExtWindows;
+
+    let mut clipboard =
+        Clipboard::new().map_err(|e| format!("failed to access clipboard: {e}"))?;
+    clipboard
+        .set()
+        .exclude_from_monitoring()
+        .text(secret)
+        .map_err(|e| format!("failed to copy to clipboard: {e}"))?;
+    Ok(clipboard)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn set_clipboard(secret: &str, _primary: bool) -> std::result::Result<Clipboard, String> {
+    let mut clipboard =
+        Clipboard::new().map_err(|e| format!("failed to access clipboard: {e}"))?;
+    clipboard
+        .set_text(secret)
+        .map_err(|e| format!("failed to copy to clipboard: {e}"))?;
+    Ok(clipboard)
+}
+
+/// How often to check whether something else has been copied meanwhile.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Stay alive (serving the selection where the platform needs it) until
+/// `deadline`, or earlier once the clipboard no longer holds `secret`.
+fn hold_until(clipboard: &mut Clipboard, secret: &str, deadline: Instant) {
+    while Instant::now() < deadline {
+        if clipboard.get_text().ok().as_deref() != Some(secret) {
+            return;
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())));
+    }
+}
+
+fn hold_until_replaced(clipboard: &mut Clipboard, secret: &str) {
+    // Platforms where the clipboard outlives its owner need nothing more.
+    if cfg!(any(target_os = "macos", windows)) {
+        return;
+    }
+    while clipboard.get_text().ok().as_deref() == Some(secret) {
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn clear_if_unchanged(clipboard: &mut Clipboard, secret: &str, primary: bool) {
+    if clipboard.get_text().ok().as_deref() == Some(secret) {
+        let _ = clipboard.clear();
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if primary {
+        use arboard::{ClearExtLinux, GetExtLinux, LinuxClipboardKind};
+
+        let held = clipboard
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+            .ok();
+        if held.as_deref() == Some(secret) {
+            let _ = clipboard.clear_with().clipboard(LinuxClipboardKind::Primary);
+        }
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let _ = primary;
 }
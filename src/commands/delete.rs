@@ -1,34 +1,25 @@
 use std::io::{self, Write};
 
-use anyhow::Result;
-
-use crate::cli::DeleteArgs;
-use crate::vault::{
+use ownkey::attachment;
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::vault::{
     ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
-    warn_if_insecure_cli_password, PasswordOptions,
 };
-use crate::vault_store;
-
-pub fn handle(args: DeleteArgs) -> Result<()> {
-    let path = if let Some(ref path) = args.path {
-        path.clone()
-    } else {
-        vault_store::default_vault_path()?
-    };
-
-    let opts = PasswordOptions {
-        password: args.password.as_deref(),
-        keychain_account: args.keychain_account.as_deref(),
-        keychain_service: &args.keychain_service,
-        vault_path: &path,
-        no_session: args.no_session,
-    };
-    ensure_vault_exists_with_password(&path, &opts)?;
-    let mut vault = load_vault_with_password(&path, &opts)?;
-
-    warn_if_insecure_cli_password(&opts);
-
-    if !vault.entries.contains_key(&args.key) {
+
+use crate::cli::DeleteArgs;
+use crate::commands::log;
+use crate::options::Options;
+
+pub fn handle(args: DeleteArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+
+    if !unlocked.vault.entries.contains_key(&args.key) {
+        let missing = Err::<(), _>(OwnkeyError::NotFound(args.key.clone()));
+        log::record(path, &unlocked, "delete", Some(&args.key), &missing);
         println!("No entry found for key {}", args.key);
         return Ok(());
     }
@@ -41,7 +32,9 @@ pub fn handle(args: DeleteArgs) -> Result<()> {
         io::stdout().flush().ok();
 
         let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        io::stdin()
+            .read_line(&mut input)
+            .map_err(io_context("failed to read confirmation"))?;
         let answer = input.trim().to_lowercase();
         if answer != "y" && answer != "yes" {
             println!("Delete cancelled.");
@@ -49,8 +42,15 @@ pub fn handle(args: DeleteArgs) -> Result<()> {
         }
     }
 
-    vault.entries.remove(&args.key);
-    save_vault_with_password(&path, &vault, &opts)?;
+    let removed = unlocked.vault.entries.remove(&args.key);
+    let saved = save_vault_with_password(path, &mut unlocked, &opts);
+    log::record(path, &unlocked, "delete", Some(&args.key), &saved);
+    saved?;
+    if let Some(stored) = removed.and_then(|entry| entry.attachment) {
+        if let Err(err) = attachment::remove(path, &stored) {
+            eprintln!("Warning: {err}");
+        }
+    }
     println!("Deleted key {}", args.key);
 
     Ok(())
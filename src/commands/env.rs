@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::models::EntryKind;
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
+
+use crate::cli::{EnvArgs, EnvFormat};
+use crate::commands::view;
+use crate::options::Options;
+
+pub fn handle(args: EnvArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    if args.keys.is_empty() && args.prefix.is_none() && args.tags.is_empty() {
+        return Err(OwnkeyError::InvalidInput(
+            "specify one or more keys, --prefix or --tag".into(),
+        ));
+    }
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    // Explicitly named keys fail if they are files; ones picked by --prefix
+    // or --tag are skipped with a note, as in `ownkey export`.
+    let mut selected: Vec<&str> = args.keys.iter().map(String::as_str).collect();
+    if args.prefix.is_some() || !args.tags.is_empty() {
+        let prefix = args.prefix.as_deref().unwrap_or("");
+        let (files, matched): (Vec<_>, Vec<_>) = vault
+            .entries
+            .iter()
+            .filter(|(k, entry)| k.starts_with(prefix) && entry.has_tags(&args.tags))
+            .partition(|(_, entry)| entry.kind == EntryKind::File);
+        if !files.is_empty() {
+            eprintln!(
+                "Skipping {} file attachment(s); use `ownkey extract` to read them.",
+                files.len()
+            );
+        }
+        selected.extend(matched.into_iter().map(|(k, _)| k.as_str()));
+    }
+
+    // Keyed by variable name so two vault keys that map to the same name are caught.
+    let mut vars: BTreeMap<String, (&str, &str)> = BTreeMap::new();
+    for key in selected {
+        let value = view::lookup(&vault, key)?;
+        let name = var_name(&args, key);
+        if let Some((other, _)) = vars.get(&name) {
+            if *other != key {
+                return Err(OwnkeyError::InvalidInput(format!(
+                    "keys '{other}' and '{key}' both map to environment variable {name}"
+                )));
+            }
+        }
+        vars.insert(name, (key, value));
+    }
+
+    for (name, (_, value)) in &vars {
+        println!("{}", render(args.format, name, value));
+    }
+    Ok(())
+}
+
+/// The variable name for `key`, after `--strip-prefix` and `--var-prefix`.
+fn var_name(args: &EnvArgs, key: &str) -> String {
+    let stem = match args.prefix {
+        Some(ref prefix) if args.strip_prefix => key.strip_prefix(prefix.as_str()).unwrap_or(key),
+        _ => key,
+    };
+    env_var_name(&format!("{}{stem}", args.var_prefix.as_deref().unwrap_or("")))
+}
+
+/// Turn a vault key into a portable environment variable name.
+///
+/// ASCII letters are upper-cased, every character outside `[A-Za-z0-9_]`
+/// becomes `_`, and a leading digit gets a `_` prefix, so `db/prod-url`
+/// becomes `DB_PROD_URL` and `1password` becomes `_1PASSWORD`.
+pub fn env_var_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+pub fn render(format: EnvFormat, name: &str, value: &str) -> String {
+    match format {
+        EnvFormat::Bash | EnvFormat::Zsh => {
+            format!("export {name}='{}'", value.replace('\'', r"'\''"))
+        }
+        EnvFormat::Fish => format!(
+            "set -gx {name} '{}'",
+            value.replace('\\', r"\\").replace('\'', r"\'")
+        ),
+        EnvFormat::Dotenv => {
+            let mut escaped = String::with_capacity(value.len());
+            for c in value.chars() {
+                match c {
+                    '\\' => escaped.push_str(r"\\"),
+                    '"' => escaped.push_str("\\\""),
+                    '$' => escaped.push_str("\\$"),
+                    '`' => escaped.push_str("\\`"),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    _ => escaped.push(c),
+                }
+            }
+            format!("{name}=\"{escaped}\"")
+        }
+        EnvFormat::Powershell => format!("$env:{name} = '{}'", value.replace('\'', "''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names_are_transformed_into_identifiers() {
+        assert_eq!(env_var_name("openai_key"), "OPENAI_KEY");
+        assert_eq!(env_var_name("db/prod-url"), "DB_PROD_URL");
+        assert_eq!(env_var_name("1password"), "_1PASSWORD");
+        assert_eq!(env_var_name("clé"), "CL_");
+    }
+
+    #[test]
+    fn values_are_quoted_for_each_shell() {
+        let value = "it's $HOME `x` \\n";
+        assert_eq!(
+            render(EnvFormat::Bash, "V", value),
+            r"export V='it'\''s $HOME `x` \n'"
+        );
+        assert_eq!(
+            render(EnvFormat::Fish, "V", value),
+            r"set -gx V 'it\'s $HOME `x` \\n'"
+        );
+        assert_eq!(
+            render(EnvFormat::Dotenv, "V", value),
+            r#"V="it's \$HOME \`x\` \\n""#
+        );
+        assert_eq!(
+            render(EnvFormat::Powershell, "V", value),
+            "$env:V = 'it''s $HOME `x` \\n'"
+        );
+    }
+}
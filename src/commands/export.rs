@@ -0,0 +1,136 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::models::{Entry, EntryKind, Vault};
+use ownkey::vault::{
+    encrypt_vault, ensure_vault_exists_with_password, load_vault_with_password, VaultKey,
+};
+use ownkey::vault_store;
+use rpassword::prompt_password;
+use serde_json::{json, Map, Value};
+
+use crate::cli::{EnvFormat, ExportArgs, ExportFormat};
+use crate::commands::env;
+use crate::options::Options;
+
+pub fn handle(args: ExportArgs, options: &Options) -> Result<()> {
+    if args.format != ExportFormat::OwnkeyEncrypted && !args.plaintext {
+        return Err(OwnkeyError::InvalidInput(
+            "this format writes secrets unencrypted; pass --plaintext to confirm".into(),
+        ));
+    }
+
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    let selected: Vec<(&String, &Entry)> = vault
+        .entries
+        .iter()
+        .filter(|(k, _)| args.prefix.as_deref().map_or(true, |p| k.starts_with(p)))
+        .filter(|(_, entry)| entry.has_tags(&args.tags))
+        .collect();
+    let (files, selected): (Vec<_>, Vec<_>) =
+        selected.into_iter().partition(|(_, entry)| entry.kind == EntryKind::File);
+    if !files.is_empty() {
+        eprintln!(
+            "Skipping {} file attachment(s); use `ownkey extract` to export them.",
+            files.len()
+        );
+    }
+
+    let typed = selected.iter().filter(|(_, entry)| !entry.fields.is_empty()).count();
+    if typed > 0 && matches!(args.format, ExportFormat::Dotenv | ExportFormat::Csv) {
+        eprintln!(
+            "Warning: dropping the fields of {typed} typed entr{} (only the primary value is written); \
+             use --format json or ownkey-encrypted to keep them.",
+            if typed == 1 { "y" } else { "ies" }
+        );
+    }
+
+    let output = match args.format {
+        ExportFormat::Json => {
+            // The same shape `ownkey import --format json` reads back.
+            let object: Map<String, Value> = selected
+                .iter()
+                .map(|(k, entry)| {
+                    let value = json!({
+                        "value": entry.value,
+                        "kind": entry.kind.to_string(),
+                        "fields": entry.fields,
+                        "tags": entry.tags,
+                    });
+                    ((*k).clone(), value)
+                })
+                .collect();
+            let mut json = serde_json::to_string_pretty(&object)
+                .map_err(|e| OwnkeyError::InvalidInput(format!("failed to serialize export: {e}")))?;
+            json.push('\n');
+            json
+        }
+        ExportFormat::Dotenv => selected
+            .iter()
+            .map(|(k, entry)| env::render(EnvFormat::Dotenv, &env::env_var_name(k), &entry.value) + "\n")
+            .collect(),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            let csv_error = |e: csv::Error| OwnkeyError::InvalidInput(format!("failed to write csv: {e}"));
+            writer.write_record(["key", "value"]).map_err(csv_error)?;
+            for (k, entry) in &selected {
+                writer.write_record([k.as_str(), entry.value.as_str()]).map_err(csv_error)?;
+            }
+            let bytes = writer
+                .into_inner()
+                .map_err(|e| OwnkeyError::InvalidInput(format!("failed to write csv: {e}")))?;
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        ExportFormat::OwnkeyEncrypted => {
+            let passphrase = match args.export_password {
+                Some(ref p) => p.clone(),
+                None => prompt_export_passphrase()?,
+            };
+            // Previous values stay behind; the recipient only gets current ones.
+            let subset = Vault {
+                entries: selected
+                    .iter()
+                    .map(|(k, entry)| {
+                        let mut entry = (*entry).clone();
+                        entry.history.clear();
+                        ((*k).clone(), entry)
+                    })
+                    .collect(),
+            };
+            let encrypted = encrypt_vault(&subset, &VaultKey::generate(&passphrase))?;
+            let mut json = serde_json::to_string_pretty(&encrypted)
+                .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize export: {e}")))?;
+            json.push('\n');
+            json
+        }
+    };
+
+    match args.output {
+        Some(ref out) => {
+            vault_store::atomic_write(Path::new(out), &output)?;
+            eprintln!("Exported {} entries to {}", selected.len(), out);
+        }
+        None => io::stdout()
+            .write_all(output.as_bytes())
+            .map_err(io_context("failed to write export"))?,
+    }
+    Ok(())
+}
+
+fn prompt_export_passphrase() -> Result<String> {
+    let first = prompt_password("Set an export passphrase: ")
+        .map_err(io_context("passphrase prompt"))?;
+    let second = prompt_password("Confirm passphrase: ")
+        .map_err(io_context("passphrase confirm prompt"))?;
+    if first != second {
+        return Err(OwnkeyError::PasswordMismatch);
+    }
+    Ok(first)
+}
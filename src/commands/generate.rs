@@ -0,0 +1,163 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ownkey::error::{OwnkeyError, Result};
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+use crate::cli::{GenerateArgs, GenerateFormat, GenerateOptions};
+
+/// BIP-39 English list: 2048 distinct words, so each word adds 11 bits.
+const WORDLIST: &str = include_str!("wordlist.txt");
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+/// Quotes, backslash and backtick are left out so values paste safely into shells.
+const SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+const AMBIGUOUS: &str = "0Oo1lI|";
+
+pub fn handle(args: GenerateArgs) -> Result<()> {
+    let (secret, bits) = generate(&args.options)?;
+    println!("{secret}");
+    eprintln!("Entropy: ~{bits:.0} bits");
+    Ok(())
+}
+
+/// Generate a secret and estimate its entropy in bits. Everything is drawn
+/// from the operating system's CSPRNG.
+pub fn generate(options: &GenerateOptions) -> Result<(String, f64)> {
+    match options.format {
+        GenerateFormat::Password => password(options),
+        GenerateFormat::Passphrase => passphrase(options.words, &options.separator),
+        GenerateFormat::Hex => {
+            let bytes = random_bytes(options.length.unwrap_or(32))?;
+            let hex = bytes.iter().map(|b| format!("{b:02x}")).collect();
+            Ok((hex, bytes.len() as f64 * 8.0))
+        }
+        GenerateFormat::Base64 => {
+            let bytes = random_bytes(options.length.unwrap_or(32))?;
+            Ok((STANDARD.encode(&bytes), bytes.len() as f64 * 8.0))
+        }
+        GenerateFormat::Uuid => {
+            let mut bytes = [0u8; 16];
+            OsRng.fill_bytes(&mut bytes);
+            let uuid = uuid::Builder::from_random_bytes(bytes).into_uuid();
+            // Six bits are fixed by the version and variant fields.
+            Ok((uuid.to_string(), 122.0))
+        }
+    }
+}
+
+fn password(options: &GenerateOptions) -> Result<(String, f64)> {
+    let classes: Vec<Vec<char>> = [
+        (!options.no_lower, LOWER),
+        (!options.no_upper, UPPER),
+        (!options.no_digits, DIGITS),
+        (!options.no_symbols, SYMBOLS),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, chars)| {
+        chars
+            .chars()
+            .filter(|c| !(options.exclude_ambiguous && AMBIGUOUS.contains(*c)))
+            .collect()
+    })
+    .collect();
+    if classes.is_empty() {
+        return Err(OwnkeyError::InvalidInput("all character classes are disabled".into()));
+    }
+
+    let length = options.length.unwrap_or(24);
+    if length < classes.len() {
+        return Err(OwnkeyError::InvalidInput(format!(
+            "length {length} is too short to include all {} character classes",
+            classes.len()
+        )));
+    }
+
+    // One character from every class, the rest from the union, then shuffle
+    // so the guaranteed characters are not always up front.
+    let pool: Vec<char> = classes.concat();
+    let mut chars: Vec<char> = classes
+        .iter()
+        .map(|class| class[OsRng.gen_range(0..class.len())])
+        .collect();
+    chars.extend((classes.len()..length).map(|_| pool[OsRng.gen_range(0..pool.len())]));
+    chars.shuffle(&mut OsRng);
+
+    Ok((chars.into_iter().collect(), length as f64 * (pool.len() as f64).log2()))
+}
+
+fn passphrase(words: usize, separator: &str) -> Result<(String, f64)> {
+    if words == 0 {
+        return Err(OwnkeyError::InvalidInput("a passphrase needs at least one word".into()));
+    }
+    let list: Vec<&str> = WORDLIST.lines().collect();
+    let chosen: Vec<&str> = (0..words)
+        .map(|_| list[OsRng.gen_range(0..list.len())])
+        .collect();
+    Ok((chosen.join(separator), words as f64 * (list.len() as f64).log2()))
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>> {
+    if len == 0 {
+        return Err(OwnkeyError::InvalidInput("length must be at least 1".into()));
+    }
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(format: GenerateFormat) -> GenerateOptions {
+        GenerateOptions {
+            format,
+            ..GenerateOptions::default()
+        }
+    }
+
+    #[test]
+    fn wordlist_has_2048_unique_words() {
+        let mut words: Vec<&str> = WORDLIST.lines().collect();
+        words.sort_unstable();
+        words.dedup();
+        assert_eq!(words.len(), 2048);
+    }
+
+    #[test]
+    fn passwords_cover_every_enabled_class() {
+        let mut opts = options(GenerateFormat::Password);
+        opts.length = Some(4);
+        opts.exclude_ambiguous = true;
+        for _ in 0..50 {
+            let (secret, _) = generate(&opts).unwrap();
+            assert_eq!(secret.chars().count(), 4);
+            assert!(secret.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(secret.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(secret.chars().any(|c| c.is_ascii_digit()));
+            assert!(secret.chars().any(|c| SYMBOLS.contains(c)));
+            assert!(!secret.chars().any(|c| AMBIGUOUS.contains(c)));
+        }
+    }
+
+    #[test]
+    fn entropy_estimates_match_format() {
+        let (phrase, bits) = generate(&options(GenerateFormat::Passphrase)).unwrap();
+        assert_eq!(phrase.split('-').count(), 6);
+        assert_eq!(bits, 66.0);
+
+        let mut hex = options(GenerateFormat::Hex);
+        hex.length = Some(16);
+        let (secret, bits) = generate(&hex).unwrap();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(bits, 128.0);
+
+        let (uuid, _) = generate(&options(GenerateFormat::Uuid)).unwrap();
+        assert_eq!(uuid.parse::<uuid::Uuid>().unwrap().get_version_num(), 4);
+    }
+}
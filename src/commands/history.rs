@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::models::Entry;
+use ownkey::vault::{
+    ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
+};
+use serde_json::json;
+
+use crate::cli::{HistoryArgs, RollbackArgs};
+use crate::commands::log;
+use crate::options::Options;
+
+pub fn handle(args: HistoryArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+    let entry = vault
+        .entries
+        .get(&args.key)
+        .ok_or_else(|| OwnkeyError::NotFound(args.key.clone()))?;
+
+    let versions = versions(entry);
+    if args.json {
+        let list: Vec<_> = versions
+            .iter()
+            .map(|(version, set_at, current)| {
+                json!({ "version": version, "set_at": set_at, "current": current })
+            })
+            .collect();
+        println!("{}", json!(list));
+    } else {
+        for (version, set_at, current) in versions {
+            let date = set_at.map_or_else(
+                || "unknown".to_string(),
+                |at| at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            );
+            let marker = if current { "  (current)" } else { "" };
+            println!("{version}  {date}{marker}");
+        }
+    }
+    Ok(())
+}
+
+/// Every version of `entry`, newest first: number, when it was set, and
+/// whether it is the current value.
+fn versions(entry: &Entry) -> Vec<(u32, Option<DateTime<Utc>>, bool)> {
+    let current = (entry.version(), entry.last_changed(), true);
+    std::iter::once(current)
+        .chain(entry.history.iter().rev().map(|v| (v.version, v.set_at, false)))
+        .collect()
+}
+
+pub fn rollback(args: RollbackArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+    let entry = unlocked
+        .vault
+        .entries
+        .get_mut(&args.key)
+        .ok_or_else(|| OwnkeyError::NotFound(args.key.clone()))?;
+    if args.to == entry.version() {
+        println!("{} is already at version {}.", args.key, args.to);
+        return Ok(());
+    }
+    let value = entry
+        .value_at(args.to)
+        .ok_or_else(|| {
+            OwnkeyError::InvalidInput(format!(
+                "{} has no version {} (see `ownkey history {}`)",
+                args.key, args.to, args.key
+            ))
+        })?
+        .to_string();
+    entry.rotate(value);
+    let version = entry.version();
+
+    let saved = save_vault_with_password(path, &mut unlocked, &opts);
+    log::record(path, &unlocked, "rollback", Some(&args.key), &saved);
+    saved?;
+    println!(
+        "Rolled {} back to the value of version {} (now version {}).",
+        args.key, args.to, version
+    );
+    Ok(())
+}
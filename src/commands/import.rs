@@ -0,0 +1,713 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::models::{validate_tag, Entry, EntryKind};
+use ownkey::vault::{
+    ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
+};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::cli::{ImportArgs, ImportFormat, OnConflict};
+use crate::options::Options;
+
+/// One secret read from an import file, with whatever metadata the format
+/// carries: logins with a username become [`EntryKind::Login`] entries.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Record {
+    key: String,
+    value: String,
+    kind: EntryKind,
+    fields: BTreeMap<String, String>,
+    tags: BTreeSet<String>,
+}
+
+impl Record {
+    fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Record {
+            key: key.into(),
+            value: value.into(),
+            ..Record::default()
+        }
+    }
+
+    /// Describe the record as a login when it has a username; the URL is
+    /// kept when it is well formed.
+    fn with_login(mut self, username: &str, url: &str) -> Self {
+        let username = username.trim();
+        if !username.is_empty() {
+            self.kind = EntryKind::Login;
+            self.fields.insert("username".into(), username.to_string());
+            let url = url.trim();
+            if !url.is_empty() && !url.chars().any(char::is_whitespace) {
+                self.fields.insert("url".into(), url.to_string());
+            }
+        }
+        self
+    }
+
+    /// Add tags from a `,` or `;` separated list, turning inner whitespace
+    /// into `-` and dropping anything that still is not a valid tag.
+    fn with_tags(mut self, tags: &str) -> Self {
+        for tag in tags.split([',', ';']) {
+            let tag = tag.split_whitespace().collect::<Vec<_>>().join("-");
+            if validate_tag(&tag).is_ok() {
+                self.tags.insert(tag);
+            }
+        }
+        self
+    }
+
+    fn has_metadata(&self) -> bool {
+        self.kind != EntryKind::Secret || !self.fields.is_empty()
+    }
+
+    fn into_entry(self) -> Entry {
+        Entry {
+            kind: self.kind,
+            fields: self.fields,
+            tags: self.tags,
+            ..Entry::created(self.value)
+        }
+    }
+}
+
+#[derive(Default)]
+struct Summary {
+    imported: usize,
+    overwritten: usize,
+    renamed: usize,
+    skipped: usize,
+}
+
+pub fn handle(args: ImportArgs, options: &Options) -> Result<()> {
+    let contents = fs::read_to_string(&args.file)
+        .map_err(io_context(format!("failed to read {}", args.file)))?;
+    let records = parse(args.format, &contents)?;
+
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+    let entries = &mut unlocked.vault.entries;
+
+    let mut summary = Summary::default();
+    let mut seen = HashSet::new();
+    for record in records {
+        if record.key.is_empty() || record.value.is_empty() {
+            println!("skipped {} (empty key or value)", display_key(&record.key));
+            summary.skipped += 1;
+            continue;
+        }
+        if !seen.insert(record.key.clone()) {
+            println!("skipped {} (duplicate key in {})", record.key, args.file);
+            summary.skipped += 1;
+            continue;
+        }
+        let original = record.key.clone();
+        let existing = entries.get(&record.key);
+        if existing.is_some_and(|entry| entry.kind == EntryKind::File)
+            && args.on_conflict == OnConflict::Overwrite
+        {
+            println!(
+                "skipped {} (file attachment; use `ownkey attach {} FILE --force` to replace it)",
+                record.key, record.key
+            );
+            summary.skipped += 1;
+            continue;
+        }
+        let (key, entry) = match (existing, args.on_conflict) {
+            (None, _) => (record.key.clone(), record.into_entry()),
+            (Some(_), OnConflict::Skip) => {
+                println!("skipped {} (already exists)", record.key);
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(existing), OnConflict::Overwrite) => {
+                let mut entry = existing.clone();
+                entry.rotate(record.value.clone());
+                entry.tags.extend(record.tags.iter().cloned());
+                if record.has_metadata() {
+                    entry.kind = record.kind;
+                    entry.fields = record.fields;
+                }
+                (record.key, entry)
+            }
+            (Some(_), OnConflict::Rename) => {
+                let renamed = (2..)
+                    .map(|n| format!("{}_{n}", record.key))
+                    .find(|k| !entries.contains_key(k))
+                    .unwrap_or_default();
+                (renamed, record.into_entry())
+            }
+        };
+        // The merged entry must still be valid for its kind, e.g. a plain
+        // value imported over a TOTP entry has to be a usable seed.
+        if let Err(err) = entry.validate() {
+            println!("skipped {key} ({err})");
+            summary.skipped += 1;
+            continue;
+        }
+        if original != key {
+            println!("renamed {original} -> {key}");
+            summary.renamed += 1;
+        } else if entries.contains_key(&key) {
+            println!("overwrote {key}");
+            summary.overwritten += 1;
+        } else {
+            println!("imported {key}");
+            summary.imported += 1;
+        }
+        entries.insert(key, entry);
+    }
+
+    let changed = summary.imported + summary.overwritten + summary.renamed > 0;
+    if changed && !args.dry_run {
+        save_vault_with_password(path, &mut unlocked, &opts)?;
+    }
+
+    println!(
+        "{} imported, {} overwritten, {} renamed, {} skipped",
+        summary.imported, summary.overwritten, summary.renamed, summary.skipped
+    );
+    if args.dry_run {
+        println!("Dry run: vault was not modified.");
+    }
+    Ok(())
+}
+
+fn display_key(key: &str) -> &str {
+    if key.is_empty() {
+        "<unnamed>"
+    } else {
+        key
+    }
+}
+
+fn parse(format: ImportFormat, contents: &str) -> Result<Vec<Record>> {
+    match format {
+        ImportFormat::Dotenv => parse_dotenv(contents),
+        ImportFormat::Json => parse_json(contents),
+        ImportFormat::Csv => parse_csv(contents),
+        ImportFormat::BitwardenJson => parse_bitwarden(contents),
+        ImportFormat::OnePasswordCsv => parse_1password(contents),
+        ImportFormat::KeepassXml => parse_keepass(contents),
+    }
+}
+
+fn invalid(format: &str, detail: impl std::fmt::Display) -> OwnkeyError {
+    OwnkeyError::InvalidInput(format!("invalid {format} input: {detail}"))
+}
+
+/// Parse `KEY=VALUE` lines. Supports `export` prefixes, `#` comments,
+/// single-quoted literals and double-quoted values with escapes; quoted
+/// values may span several lines (e.g. PEM keys).
+fn parse_dotenv(contents: &str) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    let mut chars = contents.chars().peekable();
+    let mut line = 1;
+
+    loop {
+        // Skip blank lines and comments.
+        while let Some(&c) = chars.peek() {
+            if c == '\n' {
+                line += 1;
+                chars.next();
+            } else if c.is_whitespace() {
+                chars.next();
+            } else if c == '#' {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            } else {
+                break;
+            }
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && *c != '\n') {
+            key.push(c);
+        }
+        if chars.next() != Some('=') {
+            return Err(invalid("dotenv", format!("line {line}: expected KEY=VALUE")));
+        }
+        let key = key.trim();
+        let key = key.strip_prefix("export ").unwrap_or(key).trim().to_string();
+
+        while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+
+        let mut value = String::new();
+        match chars.peek() {
+            Some('\'') => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                        None => {
+                            return Err(invalid("dotenv", format!("line {line}: unterminated quote")))
+                        }
+                    }
+                }
+            }
+            Some('"') => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('r') => value.push('\r'),
+                            Some('t') => value.push('\t'),
+                            Some(c) => value.push(c),
+                            None => {
+                                return Err(invalid("dotenv", format!("line {line}: unterminated quote")))
+                            }
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                        None => {
+                            return Err(invalid("dotenv", format!("line {line}: unterminated quote")))
+                        }
+                    }
+                }
+            }
+            _ => {
+                while let Some(c) = chars.next_if(|c| *c != '\n') {
+                    value.push(c);
+                }
+                if let Some(idx) = value.find(" #") {
+                    value.truncate(idx);
+                }
+                value = value.trim_end().to_string();
+            }
+        }
+        // Ignore anything trailing a quoted value on the same line (usually a comment).
+        while chars.next_if(|c| *c != '\n').is_some() {}
+
+        records.push(Record::new(key, value));
+    }
+    Ok(records)
+}
+
+/// An entry as written by `ownkey export --format json`.
+#[derive(Deserialize)]
+struct JsonEntry {
+    value: String,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    #[serde(default)]
+    tags: BTreeSet<String>,
+}
+
+/// Parse a JSON object of `"key": "value"` pairs, or of `"key": {"value",
+/// "kind", "fields", "tags"}` objects as written by `ownkey export`.
+fn parse_json(contents: &str) -> Result<Vec<Record>> {
+    let value: Value = serde_json::from_str(contents).map_err(|e| invalid("json", e))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid("json", "expected an object of key/value pairs"))?;
+    object
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                Value::Object(_) => return json_entry(key, value),
+                _ => return Err(invalid("json", format!("value for '{key}' must be a string"))),
+            };
+            Ok(Record::new(key.clone(), value))
+        })
+        .collect()
+}
+
+fn json_entry(key: &str, value: &Value) -> Result<Record> {
+    let entry = JsonEntry::deserialize(value)
+        .map_err(|e| invalid("json", format!("entry '{key}': {e}")))?;
+    let kind = match entry.kind {
+        Some(ref kind) => kind.parse().map_err(|e| invalid("json", format!("entry '{key}': {e}")))?,
+        None => EntryKind::Secret,
+    };
+    if kind == EntryKind::File {
+        return Err(invalid("json", format!("entry '{key}' is a file attachment; use `ownkey attach`")));
+    }
+    Ok(Record {
+        kind,
+        fields: entry.fields,
+        tags: entry.tags.into_iter().filter(|tag| validate_tag(tag).is_ok()).collect(),
+        ..Record::new(key, entry.value)
+    })
+}
+
+/// Find the first header (case-insensitive) matching one of `names`.
+fn column(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    headers
+        .iter()
+        .position(|h| names.iter().any(|n| h.trim().eq_ignore_ascii_case(n)))
+}
+
+/// Parse CSV with `key`/`name` and `value`/`secret`/`password` columns. Files
+/// without a recognised header are read as headerless `key,value` rows.
+fn parse_csv(contents: &str) -> Result<Vec<Record>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+    let mut rows = reader.records();
+    let Some(first) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let first = first.map_err(|e| invalid("csv", e))?;
+
+    let (key_col, value_col, skip_first) = match (
+        column(&first, &["key", "name"]),
+        column(&first, &["value", "secret", "password"]),
+    ) {
+        (Some(k), Some(v)) => (k, v, true),
+        _ => (0, 1, false),
+    };
+
+    let mut records = Vec::new();
+    let leading = if skip_first { None } else { Some(Ok(first)) };
+    for row in leading.into_iter().chain(rows) {
+        let row = row.map_err(|e| invalid("csv", e))?;
+        records.push(Record::new(
+            row.get(key_col).unwrap_or_default().trim(),
+            row.get(value_col).unwrap_or_default(),
+        ));
+    }
+    Ok(records)
+}
+
+/// Parse a 1Password CSV export: the entry title becomes the key and the
+/// password the value. Usernames and URLs make login entries; tags are kept.
+fn parse_1password(contents: &str) -> Result<Vec<Record>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+    let headers = reader.headers().map_err(|e| invalid("1password-csv", e))?.clone();
+    let title = column(&headers, &["title", "name"])
+        .ok_or_else(|| invalid("1password-csv", "missing Title column"))?;
+    let password = column(&headers, &["password"])
+        .ok_or_else(|| invalid("1password-csv", "missing Password column"))?;
+    let username = column(&headers, &["username"]);
+    let url = column(&headers, &["url", "website"]);
+    let tags = column(&headers, &["tags"]);
+    let cell = |row: &csv::StringRecord, col: Option<usize>| {
+        col.and_then(|c| row.get(c)).unwrap_or_default().to_string()
+    };
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row.map_err(|e| invalid("1password-csv", e))?;
+        records.push(
+            Record::new(cell(&row, Some(title)).trim(), cell(&row, Some(password)))
+                .with_login(&cell(&row, username), &cell(&row, url))
+                .with_tags(&cell(&row, tags)),
+        );
+    }
+    Ok(records)
+}
+
+#[derive(Deserialize)]
+struct BitwardenExport {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    folders: Vec<BitwardenFolder>,
+    #[serde(default)]
+    items: Vec<BitwardenItem>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenFolder {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenItem {
+    name: String,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde(default)]
+    login: Option<BitwardenLogin>,
+}
+
+#[derive(Deserialize, Default)]
+struct BitwardenLogin {
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    uris: Option<Vec<BitwardenUri>>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenUri {
+    #[serde(default)]
+    uri: Option<String>,
+}
+
+/// Parse an unencrypted Bitwarden JSON export. Logins contribute their
+/// password, username and first URL; secure notes become note entries. The
+/// item's folder becomes a tag.
+fn parse_bitwarden(contents: &str) -> Result<Vec<Record>> {
+    let export: BitwardenExport =
+        serde_json::from_str(contents).map_err(|e| invalid("bitwarden-json", e))?;
+    if export.encrypted {
+        return Err(invalid(
+            "bitwarden-json",
+            "encrypted exports are not supported; export as unencrypted JSON",
+        ));
+    }
+    let folders: BTreeMap<String, String> = export
+        .folders
+        .into_iter()
+        .map(|folder| (folder.id, folder.name))
+        .collect();
+    Ok(export
+        .items
+        .into_iter()
+        .map(|item| {
+            let folder = item
+                .folder_id
+                .and_then(|id| folders.get(&id).cloned())
+                .unwrap_or_default();
+            let login = item.login.unwrap_or_default();
+            let password = login.password.filter(|p| !p.is_empty());
+            let record = match (password, item.notes) {
+                (Some(password), _) => {
+                    let url = login
+                        .uris
+                        .unwrap_or_default()
+                        .into_iter()
+                        .find_map(|u| u.uri)
+                        .unwrap_or_default();
+                    Record::new(item.name.trim(), password)
+                        .with_login(&login.username.unwrap_or_default(), &url)
+                }
+                (None, Some(notes)) => Record {
+                    kind: EntryKind::Note,
+                    ..Record::new(item.name.trim(), notes)
+                },
+                (None, None) => Record::new(item.name.trim(), ""),
+            };
+            record.with_tags(&folder)
+        })
+        .collect())
+}
+
+/// Parse a KeePass 2 XML export: each entry's `Title` becomes the key and its
+/// `Password` the value, with `UserName`, `URL` and `<Tags>` as metadata.
+/// Previous versions kept under `<History>` are ignored.
+fn parse_keepass(contents: &str) -> Result<Vec<Record>> {
+    let mut reader = Reader::from_str(contents);
+    let mut path: Vec<String> = Vec::new();
+    let mut records = Vec::new();
+
+    let mut in_entry = false;
+    let mut title = String::new();
+    let mut password = String::new();
+    let mut username = String::new();
+    let mut url = String::new();
+    let mut tags = String::new();
+    let mut field_key = String::new();
+    let mut field_value = String::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| invalid("keepass-xml", e))?;
+        let in_history = path.iter().any(|p| p == "History");
+        match event {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if name == "Entry" && !in_history {
+                    in_entry = true;
+                    title.clear();
+                    password.clear();
+                    username.clear();
+                    url.clear();
+                    tags.clear();
+                }
+                if name == "String" {
+                    field_key.clear();
+                    field_value.clear();
+                }
+                path.push(name);
+            }
+            Event::Text(t) if in_entry && !in_history => {
+                let text = t.unescape().map_err(|e| invalid("keepass-xml", e))?;
+                match path.last().map(String::as_str) {
+                    Some("Key") => field_key.push_str(&text),
+                    Some("Value") => field_value.push_str(&text),
+                    Some("Tags") if path.iter().rev().nth(1).map(String::as_str) == Some("Entry") => {
+                        tags.push_str(&text)
+                    }
+                    _ => {}
+                }
+            }
+            Event::CData(c)
+                if in_entry && !in_history && path.last().map(String::as_str) == Some("Value") =>
+            {
+                field_value.push_str(&String::from_utf8_lossy(&c));
+            }
+            Event::End(_) => {
+                let name = path.pop().unwrap_or_default();
+                let in_history = path.iter().any(|p| p == "History");
+                if in_entry && !in_history {
+                    if name == "String" {
+                        match field_key.as_str() {
+                            "Title" => title = std::mem::take(&mut field_value),
+                            "Password" => password = std::mem::take(&mut field_value),
+                            "UserName" => username = std::mem::take(&mut field_value),
+                            "URL" => url = std::mem::take(&mut field_value),
+                            _ => {}
+                        }
+                    } else if name == "Entry" {
+                        in_entry = false;
+                        records.push(
+                            Record::new(title.trim(), std::mem::take(&mut password))
+                                .with_login(&username, &url)
+                                .with_tags(&tags),
+                        );
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(records: Vec<Record>) -> Vec<(String, String)> {
+        records.into_iter().map(|r| (r.key, r.value)).collect()
+    }
+
+    fn pair(k: &str, v: &str) -> (String, String) {
+        (k.to_string(), v.to_string())
+    }
+
+    #[test]
+    fn dotenv_handles_quotes_comments_and_multiline() {
+        let input = "# comment\nexport A=plain # trailing\nB='single $x'\nC=\"line1\\nline2 \\\"q\\\"\"\nD=\"-----BEGIN\nKEY-----\"\n\nE=\n";
+        assert_eq!(
+            pairs(parse_dotenv(input).unwrap()),
+            vec![
+                pair("A", "plain"),
+                pair("B", "single $x"),
+                pair("C", "line1\nline2 \"q\""),
+                pair("D", "-----BEGIN\nKEY-----"),
+                pair("E", ""),
+            ]
+        );
+        assert!(parse_dotenv("NOEQUALS\n").is_err());
+        assert!(parse_dotenv("A=\"open").is_err());
+        assert!(parse_dotenv("A=\"trailing\\").is_err(), "a backslash at EOF must not end the value");
+    }
+
+    #[test]
+    fn csv_detects_headers_or_falls_back_to_two_columns() {
+        let with_headers = "Name,Value\nk1,\"a,b\"\n";
+        assert_eq!(pairs(parse_csv(with_headers).unwrap()), vec![pair("k1", "a,b")]);
+        let headerless = "k1,v1\nk2,v2\n";
+        assert_eq!(
+            pairs(parse_csv(headerless).unwrap()),
+            vec![pair("k1", "v1"), pair("k2", "v2")]
+        );
+    }
+
+    #[test]
+    fn password_manager_exports_are_mapped() {
+        let onepassword = "Title,Url,Username,Password,Notes\nGitHub,https://github.com,me,gh-pass,\n";
+        assert_eq!(
+            pairs(parse_1password(onepassword).unwrap()),
+            vec![pair("GitHub", "gh-pass")]
+        );
+
+        let bitwarden = r#"{"encrypted":false,"items":[
+            {"name":"Stripe","type":1,"login":{"username":"u","password":"sk_live"}},
+            {"name":"Note","type":2,"notes":"secret note","login":null}]}"#;
+        assert_eq!(
+            pairs(parse_bitwarden(bitwarden).unwrap()),
+            vec![pair("Stripe", "sk_live"), pair("Note", "secret note")]
+        );
+
+        let keepass = r#"<KeePassFile><Root><Group><Name>Root</Name>
+            <Entry>
+              <Tags>prod</Tags>
+              <String><Key>Title</Key><Value>db &amp; cache</Value></String>
+              <String><Key>Password</Key><Value ProtectInMemory="True"> p w </Value></String>
+              <History><Entry><String><Key>Title</Key><Value>old</Value></String></Entry></History>
+            </Entry></Group></Root></KeePassFile>"#;
+        let records = parse_keepass(keepass).unwrap();
+        assert_eq!(records[0].tags, BTreeSet::from(["prod".to_string()]));
+        assert_eq!(
+            pairs(records),
+            vec![pair("db & cache", " p w ")]
+        );
+    }
+
+    #[test]
+    fn password_manager_metadata_is_kept() {
+        let onepassword = "Title,Url,Username,Password,Notes,Tags\nGitHub,https://github.com,me,gh-pass,,\"work, dev team\"\nWiFi,,,wifi-pass,,\n";
+        let records = parse_1password(onepassword).unwrap();
+        assert_eq!(records[0].kind, EntryKind::Login);
+        assert_eq!(records[0].fields["username"], "me");
+        assert_eq!(records[0].fields["url"], "https://github.com");
+        assert_eq!(
+            records[0].tags,
+            BTreeSet::from(["work".to_string(), "dev-team".to_string()])
+        );
+        assert_eq!(records[1].kind, EntryKind::Secret, "no username, no login");
+        assert!(records[1].fields.is_empty());
+
+        let bitwarden = r#"{"encrypted":false,"folders":[{"id":"f1","name":"Payments"}],"items":[
+            {"name":"Stripe","folderId":"f1","login":{"username":"ops","password":"sk","uris":[{"uri":"https://stripe.com"}]}},
+            {"name":"Note","notes":"secret note","login":null}]}"#;
+        let records = parse_bitwarden(bitwarden).unwrap();
+        assert_eq!(records[0].kind, EntryKind::Login);
+        assert_eq!(records[0].fields["url"], "https://stripe.com");
+        assert_eq!(records[0].tags, BTreeSet::from(["Payments".to_string()]));
+        assert_eq!(records[1].kind, EntryKind::Note);
+        for record in records {
+            record.into_entry().validate().unwrap();
+        }
+
+        let keepass = r#"<KeePassFile><Root><Group><Entry>
+              <String><Key>Title</Key><Value>mail</Value></String>
+              <String><Key>UserName</Key><Value>me@example.com</Value></String>
+              <String><Key>URL</Key><Value>not a url</Value></String>
+              <String><Key>Password</Key><Value>pw</Value></String>
+            </Entry></Group></Root></KeePassFile>"#;
+        let records = parse_keepass(keepass).unwrap();
+        assert_eq!(records[0].fields.get("username").map(String::as_str), Some("me@example.com"));
+        assert!(!records[0].fields.contains_key("url"), "malformed URLs are dropped");
+    }
+}
@@ -1,32 +1,129 @@
-use anyhow::Result;
+use std::collections::BTreeMap;
+
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::models::NAMESPACE_SEPARATOR;
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
 
 use crate::cli::ListArgs;
-use crate::vault::{ensure_vault_exists_with_password, load_vault_with_password, warn_if_insecure_cli_password, PasswordOptions};
-use crate::vault_store;
+use crate::options::Options;
 
-pub fn handle(args: ListArgs) -> Result<()> {
-    let path = if let Some(ref path) = args.path {
-        path.clone()
-    } else {
-        vault_store::default_vault_path()?
-    };
+pub fn handle(args: ListArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
 
-    let opts = PasswordOptions {
-        password: args.password.as_deref(),
-        keychain_account: args.keychain_account.as_deref(),
-        keychain_service: &args.keychain_service,
-        vault_path: &path,
-        no_session: args.no_session,
-    };
+    let opts = options.password_options(path)?;
 
-    warn_if_insecure_cli_password(&opts);
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
 
-    ensure_vault_exists_with_password(&path, &opts)?;
-    let vault = load_vault_with_password(&path, &opts)?;
+    let namespace = args.namespace.as_deref().unwrap_or("");
+    let keys: Vec<&str> = vault
+        .keys_in(namespace)
+        .filter(|key| vault.entries[*key].has_tags(&args.tags))
+        .map(String::as_str)
+        .collect();
+    if keys.is_empty() && !namespace.is_empty() {
+        return Err(OwnkeyError::NotFound(namespace.to_string()));
+    }
 
-    for key in vault.entries.keys() {
-        println!("{key}");
+    if args.tree {
+        for line in render_tree(&keys) {
+            println!("{line}");
+        }
+    } else {
+        for key in keys {
+            println!("{key}");
+        }
     }
 
     Ok(())
 }
+
+/// One level of the folder view. A name can be both a key and a folder,
+/// e.g. when the vault holds `prod` as well as `prod/db`.
+#[derive(Default)]
+struct Node<'a> {
+    is_key: bool,
+    children: BTreeMap<&'a str, Node<'a>>,
+}
+
+/// Render sorted keys as a `tree`-style listing with folders suffixed by `/`.
+fn render_tree(keys: &[&str]) -> Vec<String> {
+    let mut root = Node::default();
+    for key in keys {
+        let mut node = &mut root;
+        for segment in key.split(NAMESPACE_SEPARATOR) {
+            node = node.children.entry(segment).or_default();
+        }
+        node.is_key = true;
+    }
+
+    let mut lines = Vec::new();
+    for (name, node) in &root.children {
+        if node.is_key {
+            lines.push(name.to_string());
+        }
+        if !node.children.is_empty() {
+            lines.push(format!("{name}{NAMESPACE_SEPARATOR}"));
+            render_children(node, "", &mut lines);
+        }
+    }
+    lines
+}
+
+fn render_children(node: &Node<'_>, indent: &str, lines: &mut Vec<String>) {
+    // A name that is both a key and a folder gets two rows.
+    let rows: Vec<(&str, Option<&Node<'_>>)> = node
+        .children
+        .iter()
+        .flat_map(|(name, child)| {
+            let key = child.is_key.then_some((*name, None));
+            let folder = (!child.children.is_empty()).then_some((*name, Some(child)));
+            key.into_iter().chain(folder)
+        })
+        .collect();
+
+    for (i, (name, folder)) in rows.iter().enumerate() {
+        let last = i + 1 == rows.len();
+        let connector = if last { "└── " } else { "├── " };
+        match folder {
+            None => lines.push(format!("{indent}{connector}{name}")),
+            Some(child) => {
+                lines.push(format!("{indent}{connector}{name}{NAMESPACE_SEPARATOR}"));
+                let next = format!("{indent}{}", if last { "    " } else { "│   " });
+                render_children(child, &next, lines);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_groups_keys_by_namespace() {
+        let keys = ["api_key", "prod/api", "prod/db/password", "prod/db/user", "staging/api"];
+        assert_eq!(
+            render_tree(&keys),
+            vec![
+                "api_key",
+                "prod/",
+                "├── api",
+                "└── db/",
+                "    ├── password",
+                "    └── user",
+                "staging/",
+                "└── api",
+            ]
+        );
+    }
+
+    #[test]
+    fn name_that_is_key_and_folder_appears_twice() {
+        let keys = ["prod", "prod/db", "prod/db/user"];
+        assert_eq!(
+            render_tree(&keys),
+            vec!["prod", "prod/", "├── db", "└── db/", "    └── user"]
+        );
+    }
+}
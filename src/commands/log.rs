@@ -0,0 +1,97 @@
+use ownkey::access_log::{AccessLog, Record};
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password, UnlockedVault};
+
+use crate::cli::{LogAction, LogArgs};
+use crate::options::Options;
+
+pub fn handle(args: LogArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let unlocked = load_vault_with_password(path, &opts)?;
+    let Some(log) = AccessLog::open(path, &unlocked)? else {
+        println!("This vault has not been encrypted yet, so it has no access log.");
+        return Ok(());
+    };
+
+    match args.action {
+        LogAction::Verify => {
+            let count = log.verify()?;
+            println!("Access log verified: {count} records, chain intact.");
+        }
+        LogAction::Show => {
+            let (records, problem) = log.read()?;
+            if let Some(problem) = problem {
+                eprintln!(
+                    "Warning: the access log failed verification ({problem}); showing the records before it."
+                );
+            }
+            let mut selected: Vec<&Record> = records
+                .iter()
+                .filter(|r| args.key.is_none() || r.key == args.key)
+                .filter(|r| args.command.as_deref().map_or(true, |c| r.command == c))
+                .filter(|r| args.since.map_or(true, |since| r.time >= since))
+                .collect();
+            if let Some(limit) = args.limit {
+                selected.drain(..selected.len().saturating_sub(limit));
+            }
+            if args.json {
+                let json = serde_json::to_string_pretty(&selected)
+                    .map_err(|e| OwnkeyError::InvalidInput(format!("failed to encode log: {e}")))?;
+                println!("{json}");
+            } else {
+                for r in selected {
+                    println!(
+                        "{}  {}  {}@{}  {}  {}  {}",
+                        r.seq,
+                        r.time.format("%Y-%m-%d %H:%M:%S"),
+                        r.user,
+                        r.host,
+                        r.command,
+                        r.key.as_deref().unwrap_or("-"),
+                        r.result
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Record a command in the access log of the vault at `path`. Problems with
+/// the log are reported but never fail the command that is being recorded.
+pub fn record<T>(
+    path: &str,
+    unlocked: &UnlockedVault,
+    command: &str,
+    key: Option<&str>,
+    result: &Result<T>,
+) {
+    append(AccessLog::open(path, unlocked), command, key, result);
+}
+
+/// Like [`record`] for commands that do not unlock the vault: the vault key
+/// is taken from the session cache, and nothing is logged without one.
+pub fn record_from_session<T>(path: &str, command: &str, key: Option<&str>, result: &Result<T>) {
+    match AccessLog::open_from_session(path) {
+        Ok(None) => eprintln!("Note: not recorded in the access log because the vault is locked."),
+        log => append(log, command, key, result),
+    }
+}
+
+pub fn append<T>(log: Result<Option<AccessLog>>, command: &str, key: Option<&str>, result: &Result<T>) {
+    let outcome = match result {
+        Ok(_) => "ok".to_string(),
+        Err(err) => format!("error: {err}"),
+    };
+    let appended = log.and_then(|log| match log {
+        Some(log) => log.append(command, key, &outcome).map(drop),
+        None => Ok(()),
+    });
+    if let Err(err) = appended {
+        eprintln!("Warning: failed to update the access log: {err}");
+    }
+}
@@ -1,4 +1,6 @@
 pub mod add;
+pub mod update;
+pub mod attach;
 pub mod delete;
 pub mod init;
 pub mod list;
@@ -7,3 +9,19 @@ pub mod view;
 pub mod restore;
 pub mod copy;
 pub mod rotate_password;
+pub mod run;
+pub mod env;
+pub mod render;
+pub mod import;
+pub mod export;
+pub mod mv;
+pub mod tag;
+pub mod pick;
+pub mod generate;
+pub mod totp;
+pub mod policy;
+pub mod audit;
+pub mod log;
+pub mod history;
+pub mod vault;
+pub mod config;
@@ -0,0 +1,150 @@
+use ownkey::attachment;
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::models::{Entry, Vault, NAMESPACE_SEPARATOR};
+use ownkey::vault::{
+    ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
+};
+
+use crate::cli::MvArgs;
+use crate::options::Options;
+
+pub fn handle(args: MvArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+
+    let moves = plan(&unlocked.vault, &args.from, &args.to, args.force)?;
+    let entries: Vec<Entry> = moves
+        .iter()
+        .filter_map(|(old, _)| unlocked.vault.entries.remove(old))
+        .collect();
+    // `--force` can replace attached files; their blobs go once the save lands.
+    let replaced: Vec<_> = moves
+        .iter()
+        .zip(entries)
+        .filter_map(|((_, new), entry)| unlocked.vault.entries.insert(new.clone(), entry))
+        .filter_map(|entry| entry.attachment)
+        .collect();
+
+    save_vault_with_password(path, &mut unlocked, &opts)?;
+    for stored in &replaced {
+        if let Err(err) = attachment::remove(path, stored) {
+            eprintln!("Warning: {err}");
+        }
+    }
+    for (old, new) in &moves {
+        println!("Moved {old} -> {new}");
+    }
+
+    Ok(())
+}
+
+/// Work out every `(old, new)` rename before touching the vault, so a
+/// conflict anywhere in a subtree leaves the vault unchanged.
+fn plan(vault: &Vault, from: &str, to: &str, force: bool) -> Result<Vec<(String, String)>> {
+    if from.is_empty() || to.is_empty() {
+        return Err(OwnkeyError::InvalidInput("source and destination must not be empty".into()));
+    }
+
+    let moves: Vec<(String, String)> = if from.ends_with(NAMESPACE_SEPARATOR) {
+        let mut to = to.to_string();
+        if !to.ends_with(NAMESPACE_SEPARATOR) {
+            to.push(NAMESPACE_SEPARATOR);
+        }
+        if to.starts_with(from) {
+            return Err(OwnkeyError::InvalidInput(format!(
+                "cannot move namespace {from} into itself ({to})"
+            )));
+        }
+        let moves: Vec<(String, String)> = vault
+            .keys_in(from)
+            .map(|key| (key.clone(), format!("{to}{}", &key[from.len()..])))
+            .collect();
+        if moves.is_empty() {
+            return Err(OwnkeyError::NotFound(from.to_string()));
+        }
+        moves
+    } else {
+        if !vault.entries.contains_key(from) {
+            return Err(OwnkeyError::NotFound(from.to_string()));
+        }
+        // Moving a key into a namespace keeps its own name: `mv api_key prod/`
+        // gives `prod/api_key`.
+        let mut to = to.to_string();
+        if to.ends_with(NAMESPACE_SEPARATOR) {
+            to.push_str(from.rsplit(NAMESPACE_SEPARATOR).next().unwrap_or(from));
+        }
+        if from == to {
+            return Err(OwnkeyError::InvalidInput(format!("{from} is already named {to}")));
+        }
+        vec![(from.to_string(), to)]
+    };
+
+    if !force {
+        // Keys that are themselves being moved away do not count as conflicts.
+        let conflicts: Vec<&str> = moves
+            .iter()
+            .map(|(_, new)| new.as_str())
+            .filter(|new| vault.entries.contains_key(*new) && !moves.iter().any(|(old, _)| old == new))
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(OwnkeyError::InvalidInput(format!(
+                "destination already exists: {}; pass --force to overwrite",
+                conflicts.join(", ")
+            )));
+        }
+    }
+
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(keys: &[&str]) -> Vault {
+        Vault {
+            entries: keys.iter().map(|k| (k.to_string(), Entry::new("v"))).collect(),
+        }
+    }
+
+    #[test]
+    fn subtree_move_rewrites_prefix() {
+        let v = vault(&["prod/a", "prod/db/b", "production", "staging/a"]);
+        let moves = plan(&v, "prod/", "archive/prod", false).unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                ("prod/a".to_string(), "archive/prod/a".to_string()),
+                ("prod/db/b".to_string(), "archive/prod/db/b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicts_and_self_moves_are_rejected() {
+        let v = vault(&["prod/a", "archive/prod/a"]);
+        assert!(plan(&v, "prod/", "archive/prod/", false).is_err());
+        assert!(plan(&v, "prod/", "archive/prod/", true).is_ok());
+        assert!(plan(&v, "prod/", "prod/old/", true).is_err());
+        assert!(matches!(plan(&v, "missing", "x", false), Err(OwnkeyError::NotFound(_))));
+    }
+
+    #[test]
+    fn single_key_into_namespace_keeps_its_name() {
+        let v = vault(&["api_key", "dev/db", "prod/db"]);
+        assert_eq!(
+            plan(&v, "api_key", "prod/", false).unwrap(),
+            vec![("api_key".to_string(), "prod/api_key".to_string())]
+        );
+        assert_eq!(
+            plan(&v, "dev/db", "archive/", false).unwrap(),
+            vec![("dev/db".to_string(), "archive/db".to_string())]
+        );
+        assert!(plan(&v, "dev/db", "prod/", false).is_err(), "prod/db already exists");
+        assert!(plan(&v, "dev/db", "dev/", false).is_err(), "same key");
+    }
+}
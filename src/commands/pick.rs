@@ -0,0 +1,190 @@
+use std::io::{self, IsTerminal, Stderr, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::{cursor, execute, queue, terminal};
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::models::Vault;
+
+use crate::commands::search::fuzzy_score;
+
+/// A key offered by the picker, with its tags shown alongside.
+struct Candidate<'a> {
+    key: &'a str,
+    tags: Vec<&'a str>,
+}
+
+/// Let the user choose a key with a built-in fuzzy finder drawn on stderr,
+/// starting from `query`. Matching looks at key names and tags.
+pub fn pick(vault: &Vault, query: &str) -> Result<String> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(OwnkeyError::InvalidInput(
+            "no key given and no terminal for the interactive picker".into(),
+        ));
+    }
+    if vault.entries.is_empty() {
+        return Err(OwnkeyError::InvalidInput("the vault has no entries to pick from".into()));
+    }
+
+    let candidates: Vec<Candidate> = vault
+        .entries
+        .iter()
+        .map(|(key, entry)| Candidate {
+            key,
+            tags: entry.tags.iter().map(String::as_str).collect(),
+        })
+        .collect();
+
+    let mut screen = Screen::enter()?;
+    let mut query = query.to_string();
+    let mut selected = 0;
+    loop {
+        let matches = rank(&candidates, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        screen.draw(&query, &matches, selected, candidates.len())?;
+
+        let Event::Key(key) = event::read().map_err(io_context("failed to read key press"))? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match action(key) {
+            Action::Accept => {
+                if let Some(candidate) = matches.get(selected) {
+                    return Ok(candidate.key.to_string());
+                }
+            }
+            Action::Cancel => {
+                return Err(OwnkeyError::InvalidInput("no entry selected".into()));
+            }
+            Action::Up => selected = selected.saturating_sub(1),
+            Action::Down => selected += 1,
+            Action::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            Action::Type(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            Action::None => {}
+        }
+    }
+}
+
+enum Action {
+    Accept,
+    Cancel,
+    Up,
+    Down,
+    Backspace,
+    Type(char),
+    None,
+}
+
+fn action(key: KeyEvent) -> Action {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Enter => Action::Accept,
+        KeyCode::Esc => Action::Cancel,
+        KeyCode::Char('c' | 'd') if ctrl => Action::Cancel,
+        KeyCode::Up => Action::Up,
+        KeyCode::Char('p' | 'k') if ctrl => Action::Up,
+        KeyCode::Down | KeyCode::Tab => Action::Down,
+        KeyCode::Char('n' | 'j') if ctrl => Action::Down,
+        KeyCode::Backspace => Action::Backspace,
+        KeyCode::Char(c) if !ctrl => Action::Type(c),
+        _ => Action::None,
+    }
+}
+
+/// Candidates matching `query`, best first; ties keep key order.
+fn rank<'c, 'a>(candidates: &'c [Candidate<'a>], query: &str) -> Vec<&'c Candidate<'a>> {
+    let mut scored: Vec<(u32, &Candidate)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let on_key = fuzzy_score(query, candidate.key);
+            let on_tags = candidate.tags.iter().filter_map(|tag| fuzzy_score(query, tag)).max();
+            on_key.max(on_tags).map(|score| (score, candidate))
+        })
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Raw mode on an alternate screen, restored on drop so the terminal is
+/// usable again even when the picker returns early with an error.
+struct Screen {
+    out: Stderr,
+}
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode().map_err(io_context("failed to enable raw terminal mode"))?;
+        let mut out = io::stderr();
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)
+            .map_err(io_context("failed to open picker screen"))?;
+        Ok(Screen { out })
+    }
+
+    fn draw(&mut self, query: &str, matches: &[&Candidate], selected: usize, total: usize) -> Result<()> {
+        let (_, rows) = terminal::size().map_err(io_context("failed to read terminal size"))?;
+        let visible = usize::from(rows.saturating_sub(2)).max(1);
+        // Scroll so the selected row stays on screen.
+        let first = selected.saturating_sub(visible - 1);
+
+        let out = &mut self.out;
+        queue!(out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))
+            .and_then(|_| queue!(out, Print(format!("> {query}"))))
+            .and_then(|_| queue!(out, cursor::MoveTo(0, 1), Print(format!("  {}/{total}", matches.len()))))
+            .map_err(io_context("failed to draw picker"))?;
+        for (row, candidate) in matches.iter().enumerate().skip(first).take(visible) {
+            let mut line = candidate.key.to_string();
+            if !candidate.tags.is_empty() {
+                line.push_str(&format!("  [{}]", candidate.tags.join(", ")));
+            }
+            let y = (row - first + 2) as u16;
+            let result = if row == selected {
+                queue!(
+                    out,
+                    cursor::MoveTo(0, y),
+                    SetAttribute(Attribute::Reverse),
+                    Print(format!("> {line}")),
+                    SetAttribute(Attribute::Reset)
+                )
+            } else {
+                queue!(out, cursor::MoveTo(0, y), Print(format!("  {line}")))
+            };
+            result.map_err(io_context("failed to draw picker"))?;
+        }
+        out.flush().map_err(io_context("failed to draw picker"))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_by_key_and_tags() {
+        let candidates = vec![
+            Candidate { key: "disk", tags: vec![] },
+            Candidate { key: "prod/db", tags: vec!["database"] },
+            Candidate { key: "stripe_key", tags: vec!["payments"] },
+        ];
+        let keys = |query: &str| -> Vec<&str> {
+            rank(&candidates, query).iter().map(|c| c.key).collect()
+        };
+        assert_eq!(keys("sk"), vec!["stripe_key", "disk"]);
+        assert_eq!(keys("pay"), vec!["stripe_key"]);
+        assert_eq!(keys(""), vec!["disk", "prod/db", "stripe_key"]);
+    }
+}
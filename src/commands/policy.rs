@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use ownkey::config::format_duration;
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::models::Entry;
+use ownkey::vault::{
+    ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
+};
+
+use crate::cli::PolicyArgs;
+use crate::options::Options;
+
+pub fn handle(args: PolicyArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+    let entry = unlocked
+        .vault
+        .entries
+        .get_mut(&args.key)
+        .ok_or_else(|| OwnkeyError::NotFound(args.key.clone()))?;
+
+    let changed = args.expires.is_some()
+        || args.no_expiry
+        || args.rotate_every.is_some()
+        || args.no_rotation;
+    if args.expires.is_some() || args.no_expiry {
+        entry.expires_at = args.expires;
+    }
+    if args.rotate_every.is_some() || args.no_rotation {
+        entry.rotate_every = args.rotate_every;
+    }
+    print_policy(entry);
+
+    if changed {
+        save_vault_with_password(path, &mut unlocked, &opts)?;
+    }
+    Ok(())
+}
+
+fn print_policy(entry: &Entry) {
+    let date = |at: Option<DateTime<Utc>>, missing: &str| {
+        at.map_or_else(|| missing.to_string(), |at| at.format("%Y-%m-%d").to_string())
+    };
+    println!("created:      {}", date(entry.created_at, "unknown"));
+    println!("rotated:      {}", date(entry.rotated_at, "never"));
+    println!("expires:      {}", date(entry.expires_at, "never"));
+    match entry.rotate_every {
+        Some(every) => {
+            // Entries without any timestamps are due straight away.
+            let due = entry.rotation_due_at().filter(|due| *due != DateTime::<Utc>::MIN_UTC);
+            println!("rotate every: {}", format_duration(every));
+            println!("rotation due: {}", date(due, "now"));
+        }
+        None => println!("rotate every: never"),
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
+use ownkey::vault_store;
+
+use crate::cli::RenderArgs;
+use crate::commands::view;
+use crate::options::Options;
+
+/// A piece of a parsed template: literal text or a reference to a vault key.
+#[derive(Debug, PartialEq, Eq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Key(&'a str),
+}
+
+pub fn handle(args: RenderArgs, options: &Options) -> Result<()> {
+    let template = match args.input.as_deref() {
+        None | Some("-") => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(io_context("failed to read template from stdin"))?;
+            buf
+        }
+        Some(file) => fs::read_to_string(file)
+            .map_err(io_context(format!("failed to read template {file}")))?,
+    };
+    let segments = parse(&template)?;
+
+    if args.check {
+        let keys: BTreeSet<&str> = segments
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Key(k) => Some(*k),
+                Segment::Text(_) => None,
+            })
+            .collect();
+        for key in keys {
+            println!("{key}");
+        }
+        return Ok(());
+    }
+
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    let missing: BTreeSet<&str> = segments
+        .iter()
+        .filter_map(|s| match s {
+            Segment::Key(k) if !vault.entries.contains_key(*k) => Some(*k),
+            _ => None,
+        })
+        .collect();
+    if !missing.is_empty() {
+        let keys: Vec<&str> = missing.into_iter().collect();
+        return Err(OwnkeyError::NotFound(keys.join(", ")));
+    }
+
+    let mut rendered = String::with_capacity(template.len());
+    for segment in &segments {
+        match segment {
+            Segment::Text(text) => rendered.push_str(text),
+            Segment::Key(key) => rendered.push_str(view::lookup(&vault, key)?),
+        }
+    }
+
+    match args.output {
+        Some(ref out) => vault_store::atomic_write(Path::new(out), &rendered)?,
+        None => io::stdout()
+            .write_all(rendered.as_bytes())
+            .map_err(io_context("failed to write rendered template"))?,
+    }
+    Ok(())
+}
+
+/// Split a template into literal text and `{{ ownkey "key" }}` references.
+///
+/// `{{ ... }}` blocks that do not start with `ownkey` are left untouched so
+/// templates can also carry placeholders meant for other tools.
+fn parse(template: &str) -> Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    let mut literal_start = 0;
+    let mut offset = 0;
+
+    while let Some(open) = rest.find("{{") {
+        let after_open = &rest[open + 2..];
+        let Some(close) = after_open.find("}}") else {
+            break;
+        };
+        let inner = after_open[..close].trim();
+        let block_len = open + 2 + close + 2;
+
+        let arg = inner
+            .strip_prefix("ownkey")
+            .filter(|a| a.is_empty() || a.starts_with(char::is_whitespace));
+        if let Some(arg) = arg {
+            let line = template[..offset + open].matches('\n').count() + 1;
+            let key = parse_key(arg.trim()).ok_or_else(|| {
+                OwnkeyError::InvalidInput(format!(
+                    "invalid placeholder on line {line}: expected {{{{ ownkey \"key\" }}}}"
+                ))
+            })?;
+            if literal_start < offset + open {
+                segments.push(Segment::Text(&template[literal_start..offset + open]));
+            }
+            segments.push(Segment::Key(key));
+            literal_start = offset + block_len;
+        }
+
+        offset += block_len;
+        rest = &rest[block_len..];
+    }
+
+    if literal_start < template.len() {
+        segments.push(Segment::Text(&template[literal_start..]));
+    }
+    Ok(segments)
+}
+
+fn parse_key(arg: &str) -> Option<&str> {
+    let key = arg.strip_prefix('"')?.strip_suffix('"')?;
+    if key.is_empty() || key.contains('"') {
+        return None;
+    }
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_placeholders_and_keeps_foreign_blocks() {
+        let segments = parse("a: {{ownkey \"db/password\"}}\nb: {{ .Values.x }} {{  ownkey  \"k\"  }}")
+            .expect("template should parse");
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Text("a: "),
+                Segment::Key("db/password"),
+                Segment::Text("\nb: {{ .Values.x }} "),
+                Segment::Key("k"),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_placeholder_reports_line() {
+        let err = parse("ok\n{{ ownkey db }}").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "got: {err}");
+    }
+}
@@ -1,15 +1,27 @@
-use anyhow::Result;
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::vault_store;
 
-use crate::vault_store;
+use crate::commands::log;
+use crate::options::{Options, Source};
 
-pub fn handle() -> Result<()> {
+pub fn handle(options: &Options) -> Result<()> {
+    // Backups are only kept per named vault and for the default vault.
+    if let Source::Flag(flag) = options.path.source {
+        return Err(OwnkeyError::InvalidInput(format!(
+            "restore-backup does not take {flag}; use -v NAME to pick a named vault"
+        )));
+    }
     println!("This will overwrite your existing vault. Continue? (y/N)");
     let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
+    std::io::stdin()
+        .read_line(&mut input)
+        .map_err(io_context("failed to read confirmation"))?;
     if !input.trim().eq_ignore_ascii_case("y") {
         return Ok(());
     }
-    vault_store::restore_backup()?;
+    let restored = vault_store::restore_backup();
+    log::record_from_session(options.vault_path(), "restore-backup", None, &restored);
+    restored?;
     println!("Backup restored.");
     Ok(())
 }
@@ -1,33 +1,20 @@
-use anyhow::Result;
+use ownkey::access_log::AccessLog;
+use ownkey::error::Result;
+use ownkey::keychain;
+use ownkey::vault::{load_vault_with_password, prompt_new_password, save_vault_with_password};
 
 use crate::cli::RotatePasswordArgs;
-use crate::keychain;
-use crate::vault::{
-    load_vault_with_password, prompt_new_password, save_vault_with_password,
-    warn_if_insecure_cli_password, PasswordOptions,
-};
-use crate::vault_store;
+use crate::commands::log;
+use crate::options::Options;
 
-pub fn handle(args: RotatePasswordArgs) -> Result<()> {
-    let path = if let Some(ref path) = args.path {
-        path.clone()
-    } else {
-        vault_store::default_vault_path()?
-    };
+pub fn handle(args: RotatePasswordArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
 
     // First, load the existing vault with the current password/keychain/session,
     // to verify access and obtain the decrypted contents.
-    let current_opts = PasswordOptions {
-        password: args.password.as_deref(),
-        keychain_account: args.keychain_account.as_deref(),
-        keychain_service: &args.keychain_service,
-        vault_path: &path,
-        no_session: args.no_session,
-    };
+    let current_opts = options.password_options(path)?;
 
-    warn_if_insecure_cli_password(&current_opts);
-
-    let vault = load_vault_with_password(&path, &current_opts)?;
+    let mut unlocked = load_vault_with_password(path, &current_opts)?;
 
     // Determine the new password: either from CLI or via interactive prompt.
     let new_pass = if let Some(p) = args.new_password {
@@ -36,16 +23,18 @@ pub fn handle(args: RotatePasswordArgs) -> Result<()> {
         prompt_new_password()?
     };
 
-    let new_opts = PasswordOptions {
-        password: Some(&new_pass),
-        keychain_account: current_opts.keychain_account,
-        keychain_service: current_opts.keychain_service,
-        vault_path: current_opts.vault_path,
-        no_session: current_opts.no_session,
-    };
+    // Open the access log while its key is still wrapped under the old vault key.
+    let access_log = AccessLog::open(path, &unlocked);
 
-    // Re-encrypt and save the vault with the new password.
-    save_vault_with_password(&path, &vault, &new_opts)?;
+    // Re-encrypt and save the vault under a key derived from the new password.
+    unlocked.rekey(&new_pass);
+    let saved = save_vault_with_password(path, &mut unlocked, &current_opts);
+    let access_log = match (&saved, access_log) {
+        (Ok(()), Ok(Some(access_log))) => access_log.rewrap(&unlocked).map(|()| Some(access_log)),
+        (_, access_log) => access_log,
+    };
+    log::append(access_log, "rotate-password", None, &saved);
+    saved?;
 
     // Update keychain entry to reflect the new password.
     if let Some(account) = current_opts.keychain_account {
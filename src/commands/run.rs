@@ -0,0 +1,83 @@
+use std::fs;
+use std::process::Command;
+
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
+
+use crate::cli::RunArgs;
+use crate::commands::view;
+use crate::options::Options;
+
+pub fn handle(args: RunArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    // Parse every mapping before touching the vault so typos fail fast.
+    let mut mappings = Vec::new();
+    if let Some(ref file) = args.env_file {
+        let contents = fs::read_to_string(file)
+            .map_err(io_context(format!("failed to read env file {file}")))?;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            mappings.push(parse_mapping(line)?);
+        }
+    }
+    for spec in &args.env {
+        mappings.push(parse_mapping(spec)?);
+    }
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    let (program, program_args) = args
+        .command
+        .split_first()
+        .ok_or_else(|| OwnkeyError::InvalidInput("no command given to run".into()))?;
+    let mut command = Command::new(program);
+    command.args(program_args);
+    for (name, key) in &mappings {
+        command.env(name, view::lookup(&vault, key)?);
+    }
+
+    exec(command, program)
+}
+
+/// Split a `NAME=KEY` mapping into the variable name and the vault key.
+fn parse_mapping(spec: &str) -> Result<(String, String)> {
+    let invalid = || {
+        OwnkeyError::InvalidInput(format!(
+            "invalid env mapping '{spec}': expected NAME=KEY"
+        ))
+    };
+    let (name, key) = spec.split_once('=').ok_or_else(invalid)?;
+    let (name, key) = (name.trim(), key.trim());
+    if name.is_empty() || key.is_empty() || name.contains('\0') {
+        return Err(invalid());
+    }
+    Ok((name.to_string(), key.to_string()))
+}
+
+/// Replace this process with the child so signals reach it directly and its
+/// exit status becomes ours. Secrets only ever live in the child's environment.
+#[cfg(unix)]
+fn exec(mut command: Command, program: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let err = command.exec();
+    Err(io_context(format!("failed to run {program}"))(err))
+}
+
+/// Without `exec`, wait for the child and forward its exit code. Console
+/// signals such as Ctrl-C are delivered to the whole process group, so the
+/// child receives them as well.
+#[cfg(not(unix))]
+fn exec(mut command: Command, program: &str) -> Result<()> {
+    let status = command
+        .status()
+        .map_err(io_context(format!("failed to run {program}")))?;
+    std::process::exit(status.code().unwrap_or(1));
+}
@@ -1,39 +1,170 @@
-use anyhow::Result;
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
+use regex::Regex;
 
 use crate::cli::SearchArgs;
-use crate::vault::{ensure_vault_exists_with_password, load_vault_with_password, warn_if_insecure_cli_password, PasswordOptions};
-use crate::vault_store;
-
-pub fn handle(args: SearchArgs) -> Result<()> {
-    let path = if let Some(ref path) = args.path {
-        path.clone()
-    } else {
-        vault_store::default_vault_path()?
-    };
-    let keyword = args.keyword.to_lowercase();
-
-    let opts = PasswordOptions {
-        password: args.password.as_deref(),
-        keychain_account: args.keychain_account.as_deref(),
-        keychain_service: &args.keychain_service,
-        vault_path: &path,
-        no_session: args.no_session,
-    };
+use crate::options::Options;
 
-    warn_if_insecure_cli_password(&opts);
-    ensure_vault_exists_with_password(&path, &opts)?;
-    let vault = load_vault_with_password(&path, &opts)?;
+/// How the keyword is compared against a field.
+enum Matcher {
+    Substring(String),
+    Exact(String),
+    Regex(Regex),
+    Fuzzy(String),
+}
 
-    let matches = vault
-        .entries
-        .iter()
-        .filter(|(k, v)| {
-            k.to_lowercase().contains(&keyword) || v.to_lowercase().contains(&keyword)
-        });
+impl Matcher {
+    fn from_args(args: &SearchArgs) -> Result<Self> {
+        if args.exact {
+            Ok(Matcher::Exact(args.keyword.clone()))
+        } else if args.regex {
+            Regex::new(&args.keyword)
+                .map(Matcher::Regex)
+                .map_err(|e| OwnkeyError::InvalidInput(format!("invalid regex: {e}")))
+        } else if args.fuzzy {
+            Ok(Matcher::Fuzzy(args.keyword.clone()))
+        } else {
+            Ok(Matcher::Substring(args.keyword.to_lowercase()))
+        }
+    }
+
+    /// Score a match from 1 to 100, or `None` if `field` does not match.
+    fn score(&self, field: &str) -> Option<u32> {
+        match self {
+            Matcher::Exact(keyword) => (field == keyword).then_some(100),
+            Matcher::Substring(keyword) => field
+                .to_lowercase()
+                .contains(keyword.as_str())
+                .then(|| coverage(keyword.chars().count(), field)),
+            Matcher::Regex(re) => re.find(field).map(|m| coverage(m.as_str().chars().count(), field)),
+            Matcher::Fuzzy(pattern) => fuzzy_score(pattern, field),
+        }
+    }
+}
 
-    for (k, _) in matches {
-        println!("{k}");
+pub fn handle(args: SearchArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+    let matcher = Matcher::from_args(&args)?;
+
+    let opts = options.password_options(path)?;
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    let mut matches: Vec<(&str, &str, u32)> = Vec::new();
+    for (key, entry) in vault.entries.iter().filter(|(_, e)| e.has_tags(&args.tags)) {
+        if let Some(score) = matcher.score(key) {
+            matches.push((key, "key", score));
+        }
+        if args.values {
+            if let Some(score) = matcher.score(&entry.value) {
+                matches.push((key, "value", score));
+            }
+        }
+    }
+    if args.fuzzy {
+        // Stable sort keeps key order among equal scores.
+        matches.sort_by_key(|&(_, _, score)| std::cmp::Reverse(score));
+    }
+
+    for (key, field, score) in matches {
+        println!("{key}\t{field}\t{score}");
     }
 
     Ok(())
 }
+
+/// Share of `field` covered by a match of `matched` characters, as 1..=100.
+fn coverage(matched: usize, field: &str) -> u32 {
+    let total = field.chars().count().max(1);
+    ((matched * 100 / total) as u32).clamp(1, 100)
+}
+
+/// Score how well `pattern` matches `candidate` as a case-insensitive
+/// subsequence, from 1 to 100, or `None` if it is not a subsequence.
+///
+/// Characters matched at the start of a word (after `/`, `_`, `-`, `.`, a
+/// space, or a lower-to-upper case change) and runs of consecutive matches
+/// score higher, so `sk` ranks `stripe_key` above `disk`.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<u32> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    if pattern.is_empty() {
+        return Some(100);
+    }
+    let original: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let boundary = |i: usize| {
+        i == 0
+            || !original[i - 1].is_alphanumeric()
+            || (original[i - 1].is_lowercase() && original[i].is_uppercase())
+    };
+
+    // Try every start position of the first character and keep the best
+    // greedy run from there.
+    let mut best: Option<u32> = None;
+    for start in (0..lower.len()).filter(|&i| lower[i] == pattern[0]) {
+        let mut score = 1 + if boundary(start) { 2 } else { 0 };
+        let mut prev = start;
+        let mut complete = true;
+        for &wanted in &pattern[1..] {
+            match (prev + 1..lower.len()).find(|&i| lower[i] == wanted) {
+                Some(i) => {
+                    score += 1;
+                    if i == prev + 1 {
+                        score += 2;
+                    }
+                    if boundary(i) {
+                        score += 2;
+                    }
+                    prev = i;
+                }
+                None => {
+                    complete = false;
+                    break;
+                }
+            }
+        }
+        if complete {
+            best = best.max(Some(score));
+        }
+    }
+
+    // The first character earns at most 3 and each later one at most 5 (a
+    // consecutive match that also starts a word), so only a run of word
+    // starts like `aK` in `apiKey` reaches 100 and scores never flatten.
+    let max = 3 + 5 * (pattern.len() as u32 - 1);
+    best.map(|score| (score * 100 / max).clamp(1, 100))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_prefers_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("strip", "stripe_key"), Some(65));
+        assert_eq!(fuzzy_score("ak", "aKey"), Some(100));
+        assert_eq!(fuzzy_score("xyz", "stripe_key"), None);
+        let boundary = fuzzy_score("sk", "stripe_key").unwrap();
+        let buried = fuzzy_score("sk", "disk").unwrap();
+        assert!(boundary > buried, "{boundary} should beat {buried}");
+        assert!(fuzzy_score("ak", "apiKey").unwrap() > fuzzy_score("ak", "alpaka").unwrap());
+    }
+
+    #[test]
+    fn fuzzy_ranks_camel_case_runs_above_plain_prefixes() {
+        let camel = fuzzy_score("apik", "apiKey").unwrap();
+        let prefix = fuzzy_score("apik", "apikey").unwrap();
+        assert!(camel > prefix, "{camel} should beat {prefix}");
+    }
+
+    #[test]
+    fn substring_scores_by_coverage() {
+        let matcher = Matcher::Substring("gj".into());
+        assert_eq!(matcher.score("GJ"), Some(100));
+        assert_eq!(matcher.score("gj_key"), Some(33));
+        assert_eq!(matcher.score("other"), None);
+    }
+}
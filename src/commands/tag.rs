@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::vault::{
+    ensure_vault_exists_with_password, load_vault_with_password, save_vault_with_password,
+};
+
+use crate::cli::{TagCommand, TagEditArgs, TagListArgs};
+use crate::options::Options;
+
+pub fn handle(command: TagCommand, options: &Options) -> Result<()> {
+    match command {
+        TagCommand::Add(args) => edit(args, true, options),
+        TagCommand::Remove(args) => edit(args, false, options),
+        TagCommand::List(args) => list(args, options),
+    }
+}
+
+fn edit(args: TagEditArgs, add: bool, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let mut unlocked = load_vault_with_password(path, &opts)?;
+    let entry = unlocked
+        .vault
+        .entries
+        .get_mut(&args.key)
+        .ok_or_else(|| OwnkeyError::NotFound(args.key.clone()))?;
+
+    for tag in args.tags {
+        if add {
+            entry.tags.insert(tag);
+        } else {
+            entry.tags.remove(&tag);
+        }
+    }
+    let tags = entry.tags.iter().cloned().collect::<Vec<_>>().join(", ");
+
+    save_vault_with_password(path, &mut unlocked, &opts)?;
+    println!("{}: {tags}", args.key);
+    Ok(())
+}
+
+fn list(args: TagListArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    if let Some(ref key) = args.key {
+        let entry = vault
+            .entries
+            .get(key)
+            .ok_or_else(|| OwnkeyError::NotFound(key.clone()))?;
+        for tag in &entry.tags {
+            println!("{tag}");
+        }
+        return Ok(());
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for tag in vault.entries.values().flat_map(|entry| &entry.tags) {
+        *counts.entry(tag).or_default() += 1;
+    }
+    for (tag, count) in counts {
+        println!("{tag}\t{count}");
+    }
+    Ok(())
+}
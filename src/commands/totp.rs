@@ -0,0 +1,57 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ownkey::config::Config;
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::models::EntryKind;
+use ownkey::totp::Totp;
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
+
+use crate::cli::TotpArgs;
+use crate::commands::{copy, view};
+use crate::options::Options;
+
+pub fn handle(args: TotpArgs, options: &Options, cfg: &Config) -> Result<()> {
+    let path = options.vault_path();
+
+    let opts = options.password_options(path)?;
+
+    ensure_vault_exists_with_password(path, &opts)?;
+    let vault = load_vault_with_password(path, &opts)?.vault;
+
+    let seed = view::lookup(&vault, &args.key)?;
+    // Entries saved before kinds existed may still hold an otpauth:// URI.
+    if vault.entries[&args.key].kind != EntryKind::Totp && !seed.starts_with("otpauth://") {
+        return Err(OwnkeyError::InvalidInput(format!(
+            "{} is not a TOTP entry; store the seed with `ownkey add {} <seed> --kind totp`",
+            args.key, args.key
+        )));
+    }
+
+    let mut totp = Totp::parse(seed)?;
+    if let Some(digits) = args.digits {
+        totp.digits = digits;
+    }
+    if let Some(period) = args.period {
+        totp.period = period;
+    }
+    if let Some(algorithm) = args.algorithm {
+        totp.algorithm = algorithm;
+    }
+    totp.validate()?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| OwnkeyError::InvalidInput("system clock is before 1970".into()))?
+        .as_secs();
+    let code = totp.code_at(now);
+    let remaining = totp.remaining_at(now);
+
+    if args.copy {
+        copy::to_clipboard(&code, cfg.clipboard_clear_after, false)?;
+        println!("Code for '{}' copied to clipboard ({remaining}s remaining).", args.key);
+    } else {
+        println!("{code}");
+        eprintln!("{remaining}s remaining");
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use ownkey::config::{self, Config, Selection, VaultConfig};
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::vault::ensure_vault_exists_with_password;
+use serde_json::json;
+
+use crate::cli::{VaultCommand, VaultCreateArgs, VaultListArgs, VaultUseArgs};
+use crate::options::{Options, Setting, Source};
+
+pub fn handle(command: VaultCommand, options: &Options, cfg: &Config) -> Result<()> {
+    match command {
+        VaultCommand::Create(args) => create(args, options, cfg),
+        VaultCommand::List(args) => list(args, cfg),
+        VaultCommand::Use(args) => use_vault(args, cfg),
+    }
+}
+
+fn create(args: VaultCreateArgs, options: &Options, cfg: &Config) -> Result<()> {
+    if cfg.vaults.contains_key(&args.name) {
+        return Err(OwnkeyError::InvalidInput(format!(
+            "vault '{}' is already registered",
+            args.name
+        )));
+    }
+    let path = match options.path {
+        Setting { value: ref path, source: Source::Flag(_) } if Path::new(path).is_absolute() => PathBuf::from(path),
+        Setting { value: ref path, source: Source::Flag(_) } => env::current_dir()
+            .map_err(io_context("failed to read the current directory"))?
+            .join(path),
+        _ => config::named_vault_path(&args.name)?,
+    };
+    let path = path.to_string_lossy().into_owned();
+
+    let opts = options.password_options(&path)?;
+
+    let existing = Path::new(&path).exists();
+    if !existing {
+        println!("Initializing vault at {}", path);
+        ensure_vault_exists_with_password(&path, &opts)?;
+    }
+    // Only an account given for this command belongs to the new vault.
+    let keychain_account = match options.keychain_account {
+        Some(Setting { ref value, source: Source::Flag(_) }) => Some(value.clone()),
+        _ => None,
+    };
+    let settings = VaultConfig {
+        path: path.clone(),
+        keychain_account,
+        ..VaultConfig::default()
+    };
+    config::register_vault(&args.name, &settings)?;
+
+    if existing {
+        println!("Registered existing vault '{}' at {}", args.name, path);
+    } else {
+        println!("Created vault '{}'", args.name);
+    }
+    Ok(())
+}
+
+fn list(args: VaultListArgs, cfg: &Config) -> Result<()> {
+    let selected = config::selected();
+    let in_use = |name: &str| selected.filter(|s| s.name == name);
+
+    if args.json {
+        let vaults: Vec<_> = cfg
+            .vaults
+            .iter()
+            .map(|(name, settings)| {
+                json!({
+                    "name": name,
+                    "path": settings.path,
+                    "default": cfg.default_vault.as_deref() == Some(name.as_str()),
+                    "in_use": in_use(name).is_some(),
+                })
+            })
+            .collect();
+        println!("{}", json!(vaults));
+        return Ok(());
+    }
+
+    if cfg.vaults.is_empty() {
+        println!("No named vaults. Create one with `ownkey vault create NAME`.");
+        return Ok(());
+    }
+    let width = cfg.vaults.keys().map(|name| name.len()).max().unwrap_or(0);
+    for (name, settings) in &cfg.vaults {
+        let marker = if cfg.default_vault.as_deref() == Some(name.as_str()) { "*" } else { " " };
+        let note = match in_use(name).map(|s| &s.source) {
+            Some(Selection::Flag) => "  (in use: -v)".to_string(),
+            Some(Selection::Env) => "  (in use: OWNKEY_VAULT)".to_string(),
+            Some(Selection::Marker(path)) => format!("  (in use: {})", path.display()),
+            Some(Selection::Default) => "  (in use)".to_string(),
+            None => String::new(),
+        };
+        println!("{marker} {name:<width$}  {}{note}", settings.path);
+    }
+    Ok(())
+}
+
+fn use_vault(args: VaultUseArgs, cfg: &Config) -> Result<()> {
+    if !cfg.vaults.contains_key(&args.name) {
+        return Err(OwnkeyError::InvalidInput(format!(
+            "unknown vault '{}'; see `ownkey vault list`",
+            args.name
+        )));
+    }
+    config::set_default_vault(&args.name)?;
+    println!("Default vault is now '{}'", args.name);
+    Ok(())
+}
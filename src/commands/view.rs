@@ -1,42 +1,150 @@
-use anyhow::{anyhow, Result};
+use chrono::Utc;
+use ownkey::audit;
+use ownkey::error::{OwnkeyError, Result};
+use ownkey::models::{Entry, EntryKind, Vault};
+use ownkey::vault::{ensure_vault_exists_with_password, load_vault_with_password};
 use serde_json::json;
 
 use crate::cli::ViewArgs;
-use crate::vault::{ensure_vault_exists_with_password, load_vault_with_password, warn_if_insecure_cli_password, PasswordOptions};
-use crate::vault_store;
+use crate::commands::{attach, log, pick};
+use crate::options::Options;
 
-pub fn handle(args: ViewArgs) -> Result<()> {
-    let path = if let Some(ref path) = args.path {
-        path.clone()
-    } else {
-        vault_store::default_vault_path()?
-    };
+pub fn handle(args: ViewArgs, options: &Options) -> Result<()> {
+    let path = options.vault_path();
 
-    let opts = PasswordOptions {
-        password: args.password.as_deref(),
-        keychain_account: args.keychain_account.as_deref(),
-        keychain_service: &args.keychain_service,
-        vault_path: &path,
-        no_session: args.no_session,
-    };
+    let opts = options.password_options(path)?;
 
-    warn_if_insecure_cli_password(&opts);
+    ensure_vault_exists_with_password(path, &opts)?;
+    let unlocked = load_vault_with_password(path, &opts)?;
+    let vault = &unlocked.vault;
 
-    ensure_vault_exists_with_password(&path, &opts)?;
-    let vault = load_vault_with_password(&path, &opts)?;
+    let key = match args.key {
+        Some(key) if !args.pick => key,
+        query => pick::pick(vault, query.as_deref().unwrap_or(""))?,
+    };
+    if args.json && args.field.is_none() && args.version.is_none() {
+        let described = vault
+            .entries
+            .get(&key)
+            .ok_or_else(|| OwnkeyError::NotFound(key.clone()))
+            .map(|entry| describe(&key, entry));
+        log::record(path, &unlocked, "view", Some(&key), &described);
+        let described = described?;
+        warn_if_stale(&key, &vault.entries[&key]);
+        println!("{}", described);
+        return Ok(());
+    }
 
-    if let Some(value) = vault.entries.get(&args.key) {
-        if args.json {
-            let obj = json!({
-                "key": args.key,
-                "value": value,
-            });
-            println!("{}", obj);
-        } else {
-            println!("{}", value);
+    let value = match (args.version, args.field.as_deref()) {
+        (Some(version), _) => lookup_version(vault, &key, version),
+        (None, Some(field)) => lookup_field(vault, &key, field),
+        (None, None) => lookup(vault, &key),
+    };
+    log::record(path, &unlocked, "view", Some(&key), &value);
+    let value = value?;
+    let entry = &vault.entries[&key];
+    if args.version.is_none() {
+        warn_if_stale(&key, entry);
+    }
+    if args.json {
+        let mut obj = json!({
+            "key": key,
+            "value": value,
+            "version": args.version.unwrap_or_else(|| entry.version()),
+            "tags": entry.tags,
+            "kind": entry.kind.to_string(),
+        });
+        if let Some(field) = args.field {
+            obj["field"] = json!(field);
         }
-        Ok(())
+        println!("{}", obj);
     } else {
-        Err(anyhow!("No entry found for key {}", args.key))
+        println!("{}", value);
+    }
+    Ok(())
+}
+
+/// The `view --json` object for an entry: the value plus, for typed kinds,
+/// every field by name, or the attachment details for files.
+fn describe(key: &str, entry: &Entry) -> serde_json::Value {
+    let mut obj = json!({
+        "key": key,
+        "kind": entry.kind.to_string(),
+        "version": entry.version(),
+        "tags": entry.tags,
+    });
+    match entry.attachment {
+        Some(ref stored) if entry.kind == EntryKind::File => {
+            obj["attachment"] = json!({
+                "name": stored.name,
+                "size": stored.size,
+                "sha256": stored.sha256,
+            });
+        }
+        _ => {
+            obj["value"] = json!(entry.value);
+            if !matches!(entry.kind, EntryKind::Secret | EntryKind::Totp) {
+                let fields: serde_json::Map<String, serde_json::Value> = entry
+                    .kind
+                    .fields()
+                    .iter()
+                    .filter_map(|f| Some((f.name.to_string(), json!(entry.field(f.name)?))))
+                    .collect();
+                obj["fields"] = fields.into();
+            }
+        }
+    }
+    obj
+}
+
+/// Look up the value stored under `key`, failing with `NotFound` if it is
+/// missing and with `InvalidInput` if it holds a file attachment.
+pub fn lookup<'a>(vault: &'a Vault, key: &str) -> Result<&'a str> {
+    let entry = vault
+        .entries
+        .get(key)
+        .ok_or_else(|| OwnkeyError::NotFound(key.to_string()))?;
+    attach::ensure_not_file(key, entry)?;
+    Ok(&entry.value)
+}
+
+/// Look up field `field` of `key`, failing if the entry's kind has no such
+/// field or it is not set.
+fn lookup_field<'a>(vault: &'a Vault, key: &str, field: &str) -> Result<&'a str> {
+    let entry = vault
+        .entries
+        .get(key)
+        .ok_or_else(|| OwnkeyError::NotFound(key.to_string()))?;
+    attach::ensure_not_file(key, entry)?;
+    if entry.kind.field(field).is_none() {
+        return Err(OwnkeyError::InvalidInput(format!(
+            "{} entries have no '{field}' field: expected {}",
+            entry.kind,
+            entry.kind.field_names()
+        )));
+    }
+    entry
+        .field(field)
+        .ok_or_else(|| OwnkeyError::InvalidInput(format!("{key} has no {field} set")))
+}
+
+/// Look up version `version` of `key`, current or from its history.
+fn lookup_version<'a>(vault: &'a Vault, key: &str, version: u32) -> Result<&'a str> {
+    let entry = vault
+        .entries
+        .get(key)
+        .ok_or_else(|| OwnkeyError::NotFound(key.to_string()))?;
+    attach::ensure_not_file(key, entry)?;
+    entry.value_at(version).ok_or_else(|| {
+        OwnkeyError::InvalidInput(format!(
+            "{key} has no version {version} (see `ownkey history {key}`)"
+        ))
+    })
+}
+
+/// Print expiry and rotation warnings for an entry on stderr.
+pub fn warn_if_stale(key: &str, entry: &Entry) {
+    for warning in audit::warnings(entry, Utc::now()) {
+        eprintln!("Warning: '{}' {}.", key, warning);
     }
 }
@@ -1,11 +1,19 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
 use directories::BaseDirs;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+use toml_edit::{value, DocumentMut, Item, Table};
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+use crate::error::{io_context, OwnkeyError, Result};
+
+/// File in a project directory (or any parent) naming the vault to use there.
+pub const MARKER_FILE: &str = ".ownkey";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SyncProvider {
     LocalOnly,
@@ -17,28 +25,318 @@ pub enum SyncProvider {
 pub struct Config {
     #[serde(default = "default_sync_provider")]
     pub sync_provider: SyncProvider,
+    /// How long `ownkey copy` leaves a secret on the clipboard; zero keeps it.
+    #[serde(default = "default_clipboard_clear_after", deserialize_with = "deserialize_duration")]
+    pub clipboard_clear_after: Duration,
+    /// Vault used when neither `-v` nor a `.ownkey` marker picks one.
+    #[serde(default)]
+    pub default_vault: Option<String>,
+    /// Named vaults, from `[vaults.NAME]` tables.
+    #[serde(default)]
+    pub vaults: BTreeMap<String, VaultConfig>,
+    /// Default for `--password-file`.
+    #[serde(default)]
+    pub password_file: Option<String>,
+    /// Default for `--keychain-account` (macOS only).
+    #[serde(default)]
+    pub keychain_account: Option<String>,
+    /// Default for `--keychain-service` (macOS only).
+    #[serde(default)]
+    pub keychain_service: Option<String>,
+    /// Default for `--no-session`.
+    #[serde(default)]
+    pub no_session: Option<bool>,
+}
+
+/// A named vault and the settings that apply when it is in use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct VaultConfig {
+    pub path: String,
+    /// Overrides the top-level `sync_provider`.
+    #[serde(default)]
+    pub sync_provider: Option<SyncProvider>,
+    /// Remote file for the `file` sync provider.
+    #[serde(default)]
+    pub sync_path: Option<String>,
+    /// How long an unlocked key stays in the session cache (default 5m).
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub session_ttl: Option<Duration>,
+    /// Keychain account holding this vault's password (macOS only).
+    #[serde(default)]
+    pub keychain_account: Option<String>,
+    /// File holding this vault's password.
+    #[serde(default)]
+    pub password_file: Option<String>,
+}
+
+/// The named vault picked for this run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedVault {
+    pub name: String,
+    pub settings: VaultConfig,
+    pub source: Selection,
+}
+
+/// How a named vault was picked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// `-v NAME` on the command line.
+    Flag,
+    /// The `OWNKEY_VAULT` environment variable.
+    Env,
+    /// A `.ownkey` marker file.
+    Marker(PathBuf),
+    /// `default_vault` in the config.
+    Default,
+}
+
+static SELECTED: OnceLock<SelectedVault> = OnceLock::new();
+
+impl Config {
+    /// Pick the named vault: `-v NAME` first, then `OWNKEY_VAULT` (passed
+    /// in as `env`), then a `.ownkey` marker in `dir` or one of its parents,
+    /// then `default_vault`. `None` means no named vault applies and the
+    /// built-in default path is used.
+    pub fn select_vault(
+        &self,
+        flag: Option<&str>,
+        env: Option<&str>,
+        dir: &Path,
+    ) -> Result<Option<SelectedVault>> {
+        let (name, source) = if let Some(name) = flag {
+            (name.to_string(), Selection::Flag)
+        } else if let Some(name) = env {
+            (name.to_string(), Selection::Env)
+        } else if let Some((marker, name)) = find_marker(dir)? {
+            (name, Selection::Marker(marker))
+        } else if let Some(ref name) = self.default_vault {
+            (name.clone(), Selection::Default)
+        } else {
+            return Ok(None);
+        };
+        let settings = self.vaults.get(&name).cloned().ok_or_else(|| {
+            let origin = match source {
+                Selection::Flag => String::new(),
+                Selection::Env => " (from OWNKEY_VAULT)".to_string(),
+                Selection::Marker(ref marker) => format!(" (named in {})", marker.display()),
+                Selection::Default => " (default_vault in config.toml)".to_string(),
+            };
+            OwnkeyError::InvalidInput(format!("unknown vault '{name}'{origin}; see `ownkey vault list`"))
+        })?;
+        Ok(Some(SelectedVault { name, settings, source }))
+    }
+}
+
+/// Look for a `.ownkey` marker file in `dir` and its parents. Its first line
+/// that is neither blank nor a `#` comment is the vault name.
+pub fn find_marker(dir: &Path) -> Result<Option<(PathBuf, String)>> {
+    for dir in dir.ancestors() {
+        let marker = dir.join(MARKER_FILE);
+        // `~/.ownkey` is the data directory, not a marker.
+        if !marker.is_file() {
+            continue;
+        }
+        let contents = fs::read_to_string(&marker)
+            .map_err(io_context(format!("failed to read {}", marker.display())))?;
+        let name = contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .ok_or_else(|| {
+                OwnkeyError::InvalidInput(format!("{} does not name a vault", marker.display()))
+            })?;
+        return Ok(Some((marker, name.to_string())));
+    }
+    Ok(None)
+}
+
+/// Remember the vault picked for this run; later calls are ignored.
+pub fn set_selected(selected: SelectedVault) {
+    let _ = SELECTED.set(selected);
+}
+
+/// The named vault picked for this run, if any.
+pub fn selected() -> Option<&'static SelectedVault> {
+    SELECTED.get()
+}
+
+/// Settings of the selected named vault, if `vault_path` is its file.
+pub fn settings_for(vault_path: &str) -> Option<&'static VaultConfig> {
+    selected()
+        .map(|selected| &selected.settings)
+        .filter(|settings| settings.path == vault_path)
+}
+
+/// Whether `name` can name a vault: letters, digits, `-` and `_`.
+pub fn validate_vault_name(name: &str) -> std::result::Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!(
+            "invalid vault name '{name}': use letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(())
+}
+
+/// Add `[vaults.NAME]` to config.toml, keeping the rest of the file as is.
+pub fn register_vault(name: &str, settings: &VaultConfig) -> Result<()> {
+    let path = config_path()?;
+    edit_config(|doc| {
+        let vaults = doc
+            .entry("vaults")
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .ok_or_else(|| invalid_config(&path, "`vaults` is not a table"))?;
+        if vaults.contains_key(name) {
+            return Err(OwnkeyError::InvalidInput(format!("vault '{name}' is already registered")));
+        }
+        let mut table = Table::new();
+        table["path"] = value(&settings.path);
+        if let Some(ref account) = settings.keychain_account {
+            table["keychain_account"] = value(account);
+        }
+        vaults[name] = Item::Table(table);
+        Ok(())
+    })
+}
+
+/// Set `default_vault` in config.toml.
+pub fn set_default_vault(name: &str) -> Result<()> {
+    edit_config(|doc| {
+        doc["default_vault"] = value(name);
+        Ok(())
+    })
+}
+
+fn edit_config(edit: impl FnOnce(&mut DocumentMut) -> Result<()>) -> Result<()> {
+    load_or_init()?;
+    let path = config_path()?;
+    let contents = fs::read_to_string(&path)
+        .map_err(io_context(format!("failed to read config file {}", path.display())))?;
+    let mut doc: DocumentMut = contents
+        .parse()
+        .map_err(|err| invalid_config(&path, err))?;
+    edit(&mut doc)?;
+    fs::write(&path, doc.to_string())
+        .map_err(io_context(format!("failed to write config file {}", path.display())))?;
+    Ok(())
+}
+
+fn invalid_config(path: &Path, err: impl std::fmt::Display) -> OwnkeyError {
+    OwnkeyError::Config {
+        path: path.display().to_string(),
+        message: err.to_string().trim_end().to_string(),
+    }
 }
 
 fn default_sync_provider() -> SyncProvider {
     SyncProvider::LocalOnly
 }
 
+fn default_clipboard_clear_after() -> Duration {
+    Duration::from_secs(30)
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
             sync_provider: SyncProvider::LocalOnly,
+            clipboard_clear_after: default_clipboard_clear_after(),
+            default_vault: None,
+            vaults: BTreeMap::new(),
+            password_file: None,
+            keychain_account: None,
+            keychain_service: None,
+            no_session: None,
+        }
+    }
+}
+
+/// The longest duration [`parse_duration`] accepts, so that adding one to a
+/// date stays well inside the range of dates chrono can represent.
+pub const MAX_DURATION: Duration = Duration::from_secs(10_000 * 366 * 24 * 60 * 60);
+
+/// Parse a duration such as `30s`, `5m`, `12h`, `90d` or `2w`. A bare number
+/// is taken as seconds. Durations over [`MAX_DURATION`] (10000 years) are
+/// rejected.
+pub fn parse_duration(input: &str) -> std::result::Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{input}': expected e.g. 30s, 5m, 12h, 90d"))?;
+    let seconds = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        other => return Err(format!("invalid duration unit '{other}' in '{input}': use s, m, h, d or w")),
+    };
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .filter(|duration| *duration <= MAX_DURATION)
+        .ok_or_else(|| format!("duration '{input}' is too large: the limit is 10000 years"))
+}
+
+/// Format a duration in the largest unit that divides it evenly, so that
+/// [`parse_duration`] reads it back unchanged (`7776000s` becomes `90d`).
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let units = [(7 * 24 * 60 * 60, "w"), (24 * 60 * 60, "d"), (60 * 60, "h"), (60, "m")];
+    for (size, unit) in units {
+        if seconds != 0 && seconds % size == 0 {
+            return format!("{}{unit}", seconds / size);
         }
     }
+    format!("{seconds}s")
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    /// Durations are written like `"30s"`; a bare integer is seconds.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Seconds(u64),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Seconds(seconds) => parse_duration(&seconds.to_string()).map_err(serde::de::Error::custom),
+        Raw::Text(text) => parse_duration(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+fn deserialize_optional_duration<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_duration(deserializer).map(Some)
 }
 
 fn config_dir() -> Result<PathBuf> {
-    let base =
-        BaseDirs::new().ok_or_else(|| anyhow!("cannot resolve home directory for config"))?;
+    let base = BaseDirs::new().ok_or(OwnkeyError::NoHomeDir)?;
     let dir = base.home_dir().join(".ownkey");
-    fs::create_dir_all(&dir)?;
+    fs::create_dir_all(&dir).map_err(io_context(format!("failed to create {}", dir.display())))?;
     Ok(dir)
 }
 
+/// Where `ownkey vault create NAME` puts a vault without `--path`.
+pub fn named_vault_path(name: &str) -> Result<PathBuf> {
+    Ok(config_dir()?.join("vaults").join(format!("{name}.json")))
+}
+
 pub fn config_path() -> Result<PathBuf> {
     Ok(config_dir()?.join("config.toml"))
 }
@@ -57,16 +355,111 @@ pub fn load_or_init() -> Result<Config> {
 #   "http"       - sync via HTTP backend
 
 sync_provider = "local_only"
+
+# How long `ownkey copy` keeps a secret on the clipboard before clearing it
+# (e.g. "30s", "2m" or 30 for seconds; 0 keeps it until something else is
+# copied).
+clipboard_clear_after = "30s"
+
+# Named vaults are added by `ownkey vault create` and picked with `-v NAME`,
+# a `.ownkey` file in a project directory, or `default_vault`
+# (set by `ownkey vault use`). Each can override some settings:
+#
+# [vaults.work]
+# path = "/home/me/.ownkey/vaults/work.json"
+# sync_provider = "file"
+# sync_path = "/mnt/share/work-vault.json"
+# session_ttl = "15m"
+# keychain_account = "me"
+# password_file = "/run/secrets/ownkey-work"
+
+# Defaults for the global options of the same name; command-line flags and
+# OWNKEY_PASSWORD_FILE, OWNKEY_KEYCHAIN_ACCOUNT and OWNKEY_NO_SESSION win.
+# Run `ownkey config show` to see the values in effect and where they came from.
+#
+# password_file = "/run/secrets/ownkey"
+# keychain_account = "me"
+# keychain_service = "ownkey"
+# no_session = false
 "#;
         fs::write(&path, template)
-            .with_context(|| format!("failed to write default config to {}", path.display()))?;
+            .map_err(io_context(format!("failed to write default config to {}", path.display())))?;
         return Ok(default_cfg);
     }
 
     let contents = fs::read_to_string(&path)
-        .with_context(|| format!("failed to read config file {}", path.display()))?;
-    let cfg: Config = toml::from_str(&contents)
-        .with_context(|| format!("failed to parse config file {}", path.display()))?;
-    Ok(cfg)
+        .map_err(io_context(format!("failed to read config file {}", path.display())))?;
+    toml::from_str(&contents).map_err(|err| invalid_config(&path, err))
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_accept_common_units() {
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("90d"), Ok(Duration::from_secs(90 * 86_400)));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("5y").is_err());
+        assert!(parse_duration("100000000d").is_err(), "beyond chrono's date range");
+        assert!(parse_duration("18446744073709551615w").is_err());
+        assert_eq!(parse_duration("520000w"), Ok(Duration::from_secs(520_000 * 604_800)));
+    }
+
+    #[test]
+    fn formatted_durations_round_trip() {
+        for text in ["90d", "2w", "12h", "5m", "45s", "0s"] {
+            assert_eq!(format_duration(parse_duration(text).unwrap()), text);
+        }
+    }
+
+    #[test]
+    fn vaults_are_selected_by_flag_marker_then_default() {
+        let cfg: Config = toml::from_str(
+            r#"
+            default_vault = "personal"
+            [vaults.personal]
+            path = "/v/personal.json"
+            [vaults.work]
+            path = "/v/work.json"
+            session_ttl = "15m"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(cfg.vaults["work"].session_ttl, Some(Duration::from_secs(900)));
+
+        let project = std::env::temp_dir().join(format!("ownkey_marker_{}", uuid::Uuid::new_v4()));
+        let nested = project.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        let pick = |flag, dir: &Path| cfg.select_vault(flag, None, dir).unwrap().map(|s| (s.name, s.source));
+
+        assert_eq!(pick(None, &nested), Some(("personal".into(), Selection::Default)));
+        fs::write(project.join(MARKER_FILE), "# project vault\nwork\n").unwrap();
+        let marker = Selection::Marker(project.join(MARKER_FILE));
+        assert_eq!(pick(None, &nested), Some(("work".into(), marker)));
+        assert_eq!(pick(Some("personal"), &nested), Some(("personal".into(), Selection::Flag)));
+        let from_env = cfg.select_vault(None, Some("personal"), &nested).unwrap().unwrap();
+        assert_eq!(from_env.source, Selection::Env);
+        assert_eq!(cfg.select_vault(Some("work"), Some("personal"), &nested).unwrap().unwrap().name, "work");
+        assert!(matches!(
+            cfg.select_vault(Some("missing"), None, &nested),
+            Err(crate::error::OwnkeyError::InvalidInput(_))
+        ));
+        assert_eq!(Config::default().select_vault(None, None, Path::new("/")).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_clipboard_setting_uses_default() {
+        let cfg: Config = toml::from_str("sync_provider = \"local_only\"").unwrap();
+        assert_eq!(cfg.clipboard_clear_after, Duration::from_secs(30));
+        let cfg: Config = toml::from_str("clipboard_clear_after = \"0\"").unwrap();
+        assert_eq!(cfg.clipboard_clear_after, Duration::ZERO);
+        let cfg: Config = toml::from_str("clipboard_clear_after = 45").unwrap();
+        assert_eq!(cfg.clipboard_clear_after, Duration::from_secs(45));
+        assert!(toml::from_str::<Config>("clipboard_clear_after = -1").is_err());
+    }
+}
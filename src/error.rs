@@ -0,0 +1,174 @@
+use std::io;
+
+use serde_json::json;
+use thiserror::Error;
+
+use crate::sync::error::SyncError;
+
+/// Errors returned by the ownkey library API and CLI.
+///
+/// Each variant maps to a stable process exit code (see [`OwnkeyError::exit_code`])
+/// so scripts can tell failures apart without parsing messages.
+#[derive(Error, Debug)]
+pub enum OwnkeyError {
+    #[error("No entry found for key {0}")]
+    NotFound(String),
+
+    #[error("Vault not found at {0}. Run `ownkey init` to create a new encrypted vault.")]
+    VaultNotFound(String),
+
+    #[error("Vault already exists at {0}")]
+    AlreadyExists(String),
+
+    #[error("No backup found at {0}")]
+    NoBackup(String),
+
+    #[error("Vault password is incorrect or vault is corrupted.")]
+    WrongPassword,
+
+    #[error("{0}")]
+    Corrupt(String),
+
+    #[error("Vault is currently in use by another ownkey process. Please try again later.")]
+    Locked,
+
+    #[error("Permission denied: {context}")]
+    PermissionDenied {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Sync failed: {0}")]
+    SyncFailed(#[from] SyncError),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("passwords do not match")]
+    PasswordMismatch,
+
+    #[error("invalid config file {path}: {message}")]
+    Config { path: String, message: String },
+
+    #[error("keychain error: {0}")]
+    Keychain(String),
+
+    #[error("clipboard error: {0}")]
+    Clipboard(String),
+
+    #[error("cannot resolve home directory")]
+    NoHomeDir,
+
+    #[error("encryption failed: {0}")]
+    Crypto(String),
+
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, OwnkeyError>;
+
+impl OwnkeyError {
+    /// Process exit code for this error. These values are part of the CLI's
+    /// public interface and must not change between releases.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            OwnkeyError::Io { .. }
+            | OwnkeyError::Clipboard(_)
+            | OwnkeyError::Keychain(_)
+            | OwnkeyError::NoHomeDir
+            | OwnkeyError::Crypto(_) => 1,
+            OwnkeyError::InvalidInput(_)
+            | OwnkeyError::Config { .. }
+            | OwnkeyError::PasswordMismatch => 2,
+            OwnkeyError::NotFound(_) => 3,
+            OwnkeyError::WrongPassword => 4,
+            OwnkeyError::Locked => 5,
+            OwnkeyError::Corrupt(_) => 6,
+            OwnkeyError::PermissionDenied { .. } => 7,
+            OwnkeyError::SyncFailed(_) => 8,
+            OwnkeyError::VaultNotFound(_) | OwnkeyError::NoBackup(_) => 9,
+            OwnkeyError::AlreadyExists(_) => 10,
+        }
+    }
+
+    /// Short machine-readable identifier, used by `--error-format json`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OwnkeyError::NotFound(_) => "not_found",
+            OwnkeyError::VaultNotFound(_) => "vault_not_found",
+            OwnkeyError::AlreadyExists(_) => "already_exists",
+            OwnkeyError::NoBackup(_) => "no_backup",
+            OwnkeyError::WrongPassword => "wrong_password",
+            OwnkeyError::Corrupt(_) => "corrupt",
+            OwnkeyError::Locked => "locked",
+            OwnkeyError::PermissionDenied { .. } => "permission_denied",
+            OwnkeyError::SyncFailed(_) => "sync_failed",
+            OwnkeyError::InvalidInput(_) => "invalid_input",
+            OwnkeyError::PasswordMismatch => "password_mismatch",
+            OwnkeyError::Config { .. } => "config",
+            OwnkeyError::Keychain(_) => "keychain",
+            OwnkeyError::Clipboard(_) => "clipboard",
+            OwnkeyError::NoHomeDir => "no_home_dir",
+            OwnkeyError::Crypto(_) => "crypto",
+            OwnkeyError::Io { .. } => "io",
+        }
+    }
+
+    /// JSON representation written to stderr by `--error-format json`.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "error": {
+                "kind": self.kind(),
+                "exit_code": self.exit_code(),
+                "message": self.to_string(),
+            }
+        })
+    }
+}
+
+/// Build a `map_err` adapter that wraps an `io::Error` with a short description
+/// of the operation that failed. Permission errors get their own variant.
+pub fn io_context(context: impl Into<String>) -> impl FnOnce(io::Error) -> OwnkeyError {
+    let context = context.into();
+    move |source| match source.kind() {
+        io::ErrorKind::PermissionDenied => OwnkeyError::PermissionDenied { context, source },
+        _ => OwnkeyError::Io { context, source },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_are_distinct_for_scriptable_failures() {
+        let errors = [
+            OwnkeyError::NotFound("k".into()),
+            OwnkeyError::WrongPassword,
+            OwnkeyError::Locked,
+            OwnkeyError::Corrupt("bad".into()),
+            io_context("open")(io::ErrorKind::PermissionDenied.into()),
+            OwnkeyError::SyncFailed(SyncError::Unknown),
+            OwnkeyError::VaultNotFound("v".into()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(OwnkeyError::exit_code).collect();
+        assert!(codes.iter().all(|c| *c > 2), "codes 0-2 are reserved");
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len(), "each failure class needs its own code");
+    }
+
+    #[test]
+    fn json_format_includes_kind_and_code() {
+        let value = OwnkeyError::NotFound("api_key".into()).to_json();
+        assert_eq!(value["error"]["kind"], "not_found");
+        assert_eq!(value["error"]["exit_code"], 3);
+        assert_eq!(value["error"]["message"], "No entry found for key api_key");
+    }
+}
@@ -1,12 +1,12 @@
 #[cfg(target_os = "macos")]
 mod platform {
-    use anyhow::Result;
-    use security_framework::passwords::{
-        delete_generic_password, get_generic_password, set_generic_password,
-    };
+    use security_framework::passwords::{get_generic_password, set_generic_password};
+
+    use crate::error::{OwnkeyError, Result};
 
     pub fn store_password(service: &str, account: &str, password: &str) -> Result<()> {
-        set_generic_password(service, account, password.as_bytes())?;
+        set_generic_password(service, account, password.as_bytes())
+            .map_err(|err| OwnkeyError::Keychain(err.to_string()))?;
         Ok(())
     }
 
@@ -19,39 +19,28 @@ mod platform {
                 if code == -25300 {
                     return Ok(None);
                 }
-                Err(err.into())
+                Err(OwnkeyError::Keychain(err.to_string()))
             }
         }
     }
-
-    #[allow(dead_code)]
-    pub fn delete_password(service: &str, account: &str) -> Result<()> {
-        let _ = delete_generic_password(service, account);
-        Ok(())
-    }
 }
 
 #[cfg(not(target_os = "macos"))]
 mod platform {
-    use anyhow::Result;
+    use crate::error::{OwnkeyError, Result};
 
-    pub fn store_password(_service: &str, _account: &str, _password: &str) -> Result<()> {
-        anyhow::bail!("Keychain backend is only available on macOS");
+    fn unavailable() -> OwnkeyError {
+        OwnkeyError::Keychain("the keychain backend is only available on macOS".into())
     }
 
-    pub fn retrieve_password(_service: &str, _account: &str) -> Result<Option<String>> {
-        anyhow::bail!("Keychain backend is only available on macOS");
+    pub fn store_password(_service: &str, _account: &str, _password: &str) -> Result<()> {
+        Err(unavailable())
     }
 
-    pub fn delete_password(_service: &str, _account: &str) -> Result<()> {
-        anyhow::bail!("Keychain backend is only available on macOS");
+    pub fn retrieve_password(_service: &str, _account: &str) -> Result<Option<String>> {
+        Err(unavailable())
     }
 }
 
 pub use platform::{retrieve_password, store_password};
 
-// delete_password is available but not currently used by the CLI.
-// Keeping the implementation for future use (e.g., keychain cleanup commands).
-#[allow(unused_imports)]
-pub use platform::delete_password;
-
@@ -1,2 +1,37 @@
+//! ownkey as a library.
+//!
+//! The [`Vault`] handle is the entry point for embedding ownkey in other Rust
+//! tools: open a vault with an [`Unlock`] method, read and modify entries, and
+//! [`Vault::save`] to write them back. Lower-level building blocks used by the
+//! `ownkey` binary (key derivation, file locking, session cache, sync backends)
+//! are exposed in their own modules.
+//!
+//! ```no_run
+//! use ownkey::{Unlock, Vault};
+//!
+//! let vault = Vault::open("/home/me/.ownkey/vault.json", Unlock::Interactive)?;
+//! for key in vault.list() {
+//!     println!("{key}");
+//! }
+//! let token = vault.get("github_token")?;
+//! # let _ = token;
+//! # Ok::<(), ownkey::OwnkeyError>(())
+//! ```
+
+pub mod access_log;
+pub mod attachment;
+mod api;
+pub mod audit;
+pub mod breach;
+pub mod config;
+pub mod error;
+pub mod keychain;
+pub mod models;
+pub mod session;
 pub mod sync;
+pub mod totp;
+pub mod vault;
 pub mod vault_store;
+
+pub use api::{Unlock, Vault};
+pub use error::{OwnkeyError, Result};
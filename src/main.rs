@@ -1,26 +1,31 @@
 mod cli;
 mod commands;
-mod config;
-mod sync;
-mod keychain;
-mod models;
-mod session;
-mod vault;
-mod vault_store;
+mod options;
 
-use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ErrorFormat};
+use ownkey::config;
+use ownkey::error::{io_context, OwnkeyError, Result};
+use options::Options;
 
 fn main() {
-    if let Err(err) = run() {
-        eprintln!("Error: {}", err);
-        std::process::exit(1);
+    let cli = Cli::parse();
+    let error_format = cli.error_format;
+    if let Err(err) = run(cli) {
+        report_error(&err, error_format);
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn report_error(err: &OwnkeyError, format: ErrorFormat) {
+    match format {
+        ErrorFormat::Text => eprintln!("Error: {}", err),
+        ErrorFormat::Json => eprintln!("{}", err.to_json()),
     }
 }
 
-fn run() -> Result<()> {
-    let _config = match config::load_or_init() {
+fn run(cli: Cli) -> Result<()> {
+    let cfg = match config::load_or_init() {
         Ok(cfg) => cfg,
         Err(err) => {
             eprintln!("Warning: failed to load config: {}", err);
@@ -28,21 +33,53 @@ fn run() -> Result<()> {
         }
     };
 
-    let cli = Cli::parse();
+    let cwd = std::env::current_dir().map_err(io_context("failed to read the current directory"))?;
+    let requested = options::env_var(options::ENV_VAULT);
+    match cfg.select_vault(cli.global.vault.as_deref(), requested.as_deref(), &cwd) {
+        Ok(Some(selected)) => config::set_selected(selected),
+        Ok(None) => {}
+        // Leave `ownkey vault ...` usable to repair a bad selection.
+        Err(_)
+            if matches!(cli.command, Commands::Vault(_))
+                && cli.global.vault.is_none()
+                && requested.is_none() => {}
+        Err(err) => return Err(err),
+    }
+    let opts = Options::resolve(&cli.global, &cfg, options::env_var)?;
 
     match cli.command {
-        Commands::Init(args) => commands::init::handle(args)?,
-        Commands::Add(args) => commands::add::handle(args)?,
-        Commands::List(args) => commands::list::handle(args)?,
-        Commands::View(args) => commands::view::handle(args)?,
-        Commands::Copy(args) => commands::copy::handle(args)?,
-        Commands::RotatePassword(args) => commands::rotate_password::handle(args)?,
-        Commands::Delete(args) => commands::delete::handle(args)?,
-        Commands::Search(args) => commands::search::handle(args)?,
-        Commands::RestoreBackup => commands::restore::handle()?,
-        Commands::Sync(_) => cli::sync_cmd::handle_sync(),
-        Commands::Login(args) => cli::sync_cmd::handle_login(args.username.as_deref()),
-        Commands::Logout => cli::sync_cmd::handle_logout(),
+        Commands::Init(args) => commands::init::handle(args, &opts)?,
+        Commands::Add(args) => commands::add::handle(args, &opts)?,
+        Commands::Update(args) => commands::update::handle(args, &opts)?,
+        Commands::Attach(args) => commands::attach::handle(args, &opts)?,
+        Commands::Extract(args) => commands::attach::extract(args, &opts)?,
+        Commands::List(args) => commands::list::handle(args, &opts)?,
+        Commands::View(args) => commands::view::handle(args, &opts)?,
+        Commands::Copy(args) => commands::copy::handle(args, &opts, &cfg)?,
+        Commands::Run(args) => commands::run::handle(args, &opts)?,
+        Commands::Env(args) => commands::env::handle(args, &opts)?,
+        Commands::Render(args) => commands::render::handle(args, &opts)?,
+        Commands::Import(args) => commands::import::handle(args, &opts)?,
+        Commands::Export(args) => commands::export::handle(args, &opts)?,
+        Commands::Log(args) => commands::log::handle(args, &opts)?,
+        Commands::Vault(command) => commands::vault::handle(command, &opts, &cfg)?,
+        Commands::Config(command) => commands::config::handle(command, &opts)?,
+        Commands::RotatePassword(args) => commands::rotate_password::handle(args, &opts)?,
+        Commands::Delete(args) => commands::delete::handle(args, &opts)?,
+        Commands::Tag(command) => commands::tag::handle(command, &opts)?,
+        Commands::Totp(args) => commands::totp::handle(args, &opts, &cfg)?,
+        Commands::Generate(args) => commands::generate::handle(args)?,
+        Commands::History(args) => commands::history::handle(args, &opts)?,
+        Commands::Rollback(args) => commands::history::rollback(args, &opts)?,
+        Commands::Policy(args) => commands::policy::handle(args, &opts)?,
+        Commands::Audit(args) => commands::audit::handle(args, &opts)?,
+        Commands::Mv(args) => commands::mv::handle(args, &opts)?,
+        Commands::Search(args) => commands::search::handle(args, &opts)?,
+        Commands::RestoreBackup => commands::restore::handle(&opts)?,
+        Commands::ClipboardHelper(args) => commands::copy::helper(args)?,
+        Commands::Sync(_) => cli::sync_cmd::handle_sync(&opts)?,
+        Commands::Login(args) => cli::sync_cmd::handle_login(args.username.as_deref())?,
+        Commands::Logout => cli::sync_cmd::handle_logout()?,
     }
 
     Ok(())
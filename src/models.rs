@@ -1,9 +1,531 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::error::OwnkeyError;
+
+/// Separator between namespace segments in key names, e.g. `prod/db/password`.
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// How many previous values each entry keeps in its history.
+pub const HISTORY_LIMIT: usize = 10;
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Vault {
-    pub entries: HashMap<String, String>,
+    #[serde(deserialize_with = "deserialize_entries")]
+    pub entries: BTreeMap<String, Entry>,
+}
+
+/// A stored secret and the metadata attached to it.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "EntryKind::is_secret")]
+    pub kind: EntryKind,
+    /// When the entry was first stored (unknown for entries from older vaults).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the value last changed after creation; `None` means never rotated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// How often the value should be rotated, stored like `"90d"`.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration_text")]
+    pub rotate_every: Option<Duration>,
+    /// Previous values, oldest first, at most [`HISTORY_LIMIT`] of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Version>,
+    /// Fields of typed kinds other than the primary one, which is `value`
+    /// (see [`EntryKind::fields`]).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// The stored file, for entries of kind [`EntryKind::File`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+}
+
+/// A file kept outside the vault as an encrypted blob (see [`crate::attachment`]).
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// Name of the blob file next to the vault.
+    pub id: String,
+    /// Original file name, e.g. `server.pem`.
+    pub name: String,
+    pub size: u64,
+    /// Hex SHA-256 of the file contents.
+    pub sha256: String,
+    /// Base64 key the blob is encrypted with; it only exists inside the vault.
+    pub key: String,
+}
+
+/// A previous value of an entry.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Version {
+    /// Version number, counting from 1 for the first value stored.
+    pub version: u32,
+    pub value: String,
+    /// When this value was set (unknown for values from older vaults).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_at: Option<DateTime<Utc>>,
+}
+
+/// What an entry's value holds, which decides how commands treat it.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// An opaque secret such as a password or API key.
+    #[default]
+    Secret,
+    /// A TOTP seed: an `otpauth://` URI or a base32 secret (see [`crate::totp`]).
+    Totp,
+    /// A file stored with `ownkey attach`; the value is empty.
+    File,
+    /// A website or service login: password, username and URL.
+    Login,
+    /// An API credential: key, secret and endpoint.
+    ApiKey,
+    /// Database credentials: password, host, port, user and database name.
+    Database,
+    /// An SSH key pair: private key, public key and passphrase.
+    SshKey,
+    /// Free-form secure text.
+    Note,
+}
+
+/// One field of an entry kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub required: bool,
+    /// Whether the field holds secret material that displays should mask.
+    pub secret: bool,
+}
+
+const fn field(name: &'static str, required: bool, secret: bool) -> Field {
+    Field { name, required, secret }
+}
+
+const SECRET_FIELDS: &[Field] = &[field("value", false, true)];
+const TOTP_FIELDS: &[Field] = &[field("seed", true, true)];
+const LOGIN_FIELDS: &[Field] = &[
+    field("password", true, true),
+    field("username", true, false),
+    field("url", false, false),
+];
+const API_KEY_FIELDS: &[Field] = &[
+    field("key", true, true),
+    field("secret", false, true),
+    field("endpoint", false, false),
+];
+const DATABASE_FIELDS: &[Field] = &[
+    field("password", true, true),
+    field("host", true, false),
+    field("port", false, false),
+    field("user", true, false),
+    field("name", false, false),
+];
+const SSH_KEY_FIELDS: &[Field] = &[
+    field("private_key", true, true),
+    field("public_key", false, false),
+    field("passphrase", false, true),
+];
+const NOTE_FIELDS: &[Field] = &[field("text", true, true)];
+
+impl EntryKind {
+    pub const ALL: [EntryKind; 8] = [
+        EntryKind::Secret,
+        EntryKind::Totp,
+        EntryKind::File,
+        EntryKind::Login,
+        EntryKind::ApiKey,
+        EntryKind::Database,
+        EntryKind::SshKey,
+        EntryKind::Note,
+    ];
+
+    pub fn is_secret(&self) -> bool {
+        *self == EntryKind::Secret
+    }
+
+    /// Whether the value is a password or key that strength and breach
+    /// checks apply to.
+    pub fn has_password_value(&self) -> bool {
+        matches!(
+            self,
+            EntryKind::Secret | EntryKind::Login | EntryKind::ApiKey | EntryKind::Database
+        )
+    }
+
+    /// The fields of this kind. The first is the primary field, stored as
+    /// the entry's `value` so history, `copy`, `env` and `run` use it.
+    pub fn fields(&self) -> &'static [Field] {
+        match self {
+            EntryKind::Secret => SECRET_FIELDS,
+            EntryKind::Totp => TOTP_FIELDS,
+            EntryKind::File => &[],
+            EntryKind::Login => LOGIN_FIELDS,
+            EntryKind::ApiKey => API_KEY_FIELDS,
+            EntryKind::Database => DATABASE_FIELDS,
+            EntryKind::SshKey => SSH_KEY_FIELDS,
+            EntryKind::Note => NOTE_FIELDS,
+        }
+    }
+
+    /// The field held in the entry's `value`, if the kind has one.
+    pub fn primary_field(&self) -> Option<&'static str> {
+        self.fields().first().map(|f| f.name)
+    }
+
+    pub fn field(&self, name: &str) -> Option<Field> {
+        self.fields().iter().copied().find(|f| f.name == name)
+    }
+
+    /// The field names, comma-separated, for error messages.
+    pub fn field_names(&self) -> String {
+        let names: Vec<&str> = self.fields().iter().map(|f| f.name).collect();
+        names.join(", ")
+    }
+}
+
+impl FromStr for EntryKind {
+    type Err = OwnkeyError;
+
+    fn from_str(name: &str) -> Result<Self, OwnkeyError> {
+        match name {
+            "secret" => Ok(EntryKind::Secret),
+            "totp" => Ok(EntryKind::Totp),
+            "file" => Ok(EntryKind::File),
+            "login" => Ok(EntryKind::Login),
+            "api_key" => Ok(EntryKind::ApiKey),
+            "database" => Ok(EntryKind::Database),
+            "ssh_key" => Ok(EntryKind::SshKey),
+            "note" => Ok(EntryKind::Note),
+            other => {
+                let kinds: Vec<String> = EntryKind::ALL.iter().map(|k| k.to_string()).collect();
+                Err(OwnkeyError::InvalidInput(format!(
+                    "unknown entry kind '{other}': expected one of {}",
+                    kinds.join(", ")
+                )))
+            }
+        }
+    }
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EntryKind::Secret => "secret",
+            EntryKind::Totp => "totp",
+            EntryKind::File => "file",
+            EntryKind::Login => "login",
+            EntryKind::ApiKey => "api_key",
+            EntryKind::Database => "database",
+            EntryKind::SshKey => "ssh_key",
+            EntryKind::Note => "note",
+        })
+    }
+}
+
+impl Entry {
+    pub fn new(value: impl Into<String>) -> Self {
+        Entry {
+            value: value.into(),
+            ..Entry::default()
+        }
+    }
+
+    /// A new entry stamped with the current time as its creation time.
+    pub fn created(value: impl Into<String>) -> Self {
+        Entry {
+            created_at: Some(Utc::now()),
+            ..Entry::new(value)
+        }
+    }
+
+    /// Replace the value, recording a rotation if it actually changed. The
+    /// old value moves into the history, dropping the oldest beyond the limit.
+    pub fn rotate(&mut self, value: impl Into<String>) {
+        let value = value.into();
+        if value != self.value {
+            let previous = Version {
+                version: self.version(),
+                value: std::mem::replace(&mut self.value, value),
+                set_at: self.last_changed(),
+            };
+            self.history.push(previous);
+            let excess = self.history.len().saturating_sub(HISTORY_LIMIT);
+            self.history.drain(..excess);
+            self.rotated_at = Some(Utc::now());
+        }
+    }
+
+    /// The version number of the current value.
+    pub fn version(&self) -> u32 {
+        self.history.last().map_or(1, |last| last.version + 1)
+    }
+
+    /// The value of version `number`, whether current or from the history.
+    pub fn value_at(&self, number: u32) -> Option<&str> {
+        if number == self.version() {
+            return Some(&self.value);
+        }
+        self.history
+            .iter()
+            .find(|v| v.version == number)
+            .map(|v| v.value.as_str())
+    }
+
+    /// When the value was last set: the last rotation, else creation.
+    pub fn last_changed(&self) -> Option<DateTime<Utc>> {
+        self.rotated_at.or(self.created_at)
+    }
+
+    /// When the next rotation is due, if the entry has a rotation policy.
+    /// Entries with a policy but no known timestamps are due immediately; a
+    /// due date past the end of time means never.
+    pub fn rotation_due_at(&self) -> Option<DateTime<Utc>> {
+        let every = chrono::Duration::from_std(self.rotate_every?).ok()?;
+        match self.last_changed() {
+            Some(changed) => changed.checked_add_signed(every),
+            None => Some(DateTime::<Utc>::MIN_UTC),
+        }
+    }
+
+    /// The value of field `name`: the primary field reads `value`, the rest
+    /// come from `fields`. `None` for fields the kind lacks or that are unset.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        if self.kind.primary_field() == Some(name) {
+            return Some(&self.value);
+        }
+        self.kind.field(name)?;
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// Check the entry against its kind: only known fields, required ones
+    /// present, and well-formed TOTP seeds, ports, endpoints and SSH keys.
+    pub fn validate(&self) -> Result<(), OwnkeyError> {
+        let kind = self.kind;
+        let invalid = |message: String| Err(OwnkeyError::InvalidInput(message));
+        if kind == EntryKind::Totp {
+            crate::totp::Totp::parse(&self.value)?;
+        }
+        for name in self.fields.keys() {
+            if kind.field(name).is_none() || kind.primary_field() == Some(name.as_str()) {
+                return invalid(format!(
+                    "unknown field '{name}' for a {kind} entry: expected {}",
+                    kind.field_names()
+                ));
+            }
+        }
+        for field in kind.fields().iter().filter(|f| f.required) {
+            if self.field(field.name).map_or(true, str::is_empty) {
+                return invalid(format!("a {kind} entry needs a {} field", field.name));
+            }
+        }
+        for (name, value) in kind.fields().iter().filter_map(|f| Some((f.name, self.field(f.name)?))) {
+            let problem = match name {
+                "port" if !value.parse::<u16>().is_ok_and(|port| port > 0) => {
+                    Some("must be a number from 1 to 65535")
+                }
+                "endpoint" if !(value.starts_with("https://") || value.starts_with("http://")) => {
+                    Some("must be an http:// or https:// URL")
+                }
+                "url" if value.chars().any(char::is_whitespace) => Some("must not contain spaces"),
+                "private_key"
+                    if !(value.trim_start().starts_with("-----BEGIN ")
+                        && value.contains("PRIVATE KEY-----")) =>
+                {
+                    Some("must be a PEM or OpenSSH private key (-----BEGIN ... PRIVATE KEY-----)")
+                }
+                "public_key" if value.split_whitespace().count() < 2 => {
+                    Some("must look like `ssh-ed25519 AAAA... comment`")
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                return invalid(format!("field '{name}' {problem}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the entry carries every tag in `tags` (an empty filter matches all).
+    pub fn has_tags<S: AsRef<str>>(&self, tags: &[S]) -> bool {
+        tags.iter().all(|t| self.tags.contains(t.as_ref()))
+    }
+}
+
+impl From<String> for Entry {
+    fn from(value: String) -> Self {
+        Entry::new(value)
+    }
 }
 
+impl From<&str> for Entry {
+    fn from(value: &str) -> Self {
+        Entry::new(value)
+    }
+}
+
+/// Vaults written before entries had metadata store each value as a bare
+/// string; accept both shapes so old vaults keep opening.
+fn deserialize_entries<'de, D>(deserializer: D) -> Result<BTreeMap<String, Entry>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredEntry {
+        Plain(String),
+        Full(Box<Entry>),
+    }
+
+    let stored = BTreeMap::<String, StoredEntry>::deserialize(deserializer)?;
+    Ok(stored
+        .into_iter()
+        .map(|(key, entry)| {
+            let entry = match entry {
+                StoredEntry::Plain(value) => Entry::new(value),
+                StoredEntry::Full(entry) => *entry,
+            };
+            (key, entry)
+        })
+        .collect())
+}
+
+mod duration_text {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::config::{format_duration, parse_duration};
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => serializer.serialize_str(&format_duration(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| parse_duration(&text).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Check that a tag is usable on the command line and in filters.
+pub fn validate_tag(tag: &str) -> Result<(), String> {
+    if tag.is_empty() {
+        return Err("tag must not be empty".into());
+    }
+    if tag.chars().any(|c| c.is_whitespace() || c == ',') {
+        return Err(format!("tag '{tag}' must not contain whitespace or commas"));
+    }
+    Ok(())
+}
+
+impl Vault {
+    /// Keys inside `namespace`, in sorted order.
+    ///
+    /// `prod/` and `prod` both select `prod/db` and `prod/api/key`; `prod`
+    /// additionally selects a key named exactly `prod`. An empty namespace
+    /// selects every key.
+    pub fn keys_in<'a, 'n>(&'a self, namespace: &'n str) -> impl Iterator<Item = &'a String> + 'n
+    where
+        'a: 'n,
+    {
+        self.entries
+            .keys()
+            .filter(move |key| in_namespace(key, namespace))
+    }
+}
+
+/// Whether `key` lives in `namespace` (see [`Vault::keys_in`]).
+pub fn in_namespace(key: &str, namespace: &str) -> bool {
+    if namespace.is_empty() || namespace.ends_with(NAMESPACE_SEPARATOR) {
+        return key.starts_with(namespace);
+    }
+    key == namespace
+        || key
+            .strip_prefix(namespace)
+            .is_some_and(|rest| rest.starts_with(NAMESPACE_SEPARATOR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_string_entries_still_deserialize() {
+        let vault: Vault = serde_json::from_str(
+            r#"{"entries":{"old":"v1","new":{"value":"v2","tags":["prod"]}}}"#,
+        )
+        .expect("mixed entries should parse");
+        assert_eq!(vault.entries["old"], Entry::new("v1"));
+        assert_eq!(vault.entries["new"].value, "v2");
+        assert!(vault.entries["new"].has_tags(&["prod"]));
+        assert!(!vault.entries["old"].has_tags(&["prod"]));
+    }
+
+    #[test]
+    fn typed_entries_are_validated_against_their_kind() {
+        let mut db = Entry {
+            kind: EntryKind::Database,
+            ..Entry::new("hunter2")
+        };
+        assert!(db.validate().is_err(), "host and user are required");
+        db.fields.insert("host".into(), "db.internal".into());
+        db.fields.insert("user".into(), "app".into());
+        db.validate().unwrap();
+        assert_eq!(db.field("password"), Some("hunter2"));
+        assert_eq!(db.field("host"), Some("db.internal"));
+        assert_eq!(db.field("port"), None);
+        assert_eq!(db.field("username"), None);
+
+        db.fields.insert("port".into(), "99999".into());
+        assert!(db.validate().is_err());
+        db.fields.insert("port".into(), "5432".into());
+        db.fields.insert("username".into(), "app".into());
+        assert!(db.validate().is_err(), "username is a login field");
+
+        let key = Entry {
+            kind: EntryKind::SshKey,
+            ..Entry::new("ssh-ed25519 AAAA me@host")
+        };
+        assert!(key.validate().is_err());
+        let totp = Entry {
+            kind: EntryKind::Totp,
+            ..Entry::new("not base32!")
+        };
+        assert!(totp.validate().is_err());
+        assert_eq!("ssh_key".parse::<EntryKind>().unwrap(), EntryKind::SshKey);
+        assert!("ssh".parse::<EntryKind>().is_err());
+    }
+
+    #[test]
+    fn rotation_keeps_a_bounded_history() {
+        let mut entry = Entry::created("v1");
+        entry.rotate("v1");
+        assert_eq!((entry.version(), entry.history.len()), (1, 0));
+
+        for i in 2..=HISTORY_LIMIT as u32 + 3 {
+            entry.rotate(format!("v{i}"));
+        }
+        let current = HISTORY_LIMIT as u32 + 3;
+        assert_eq!(entry.version(), current);
+        assert_eq!(entry.history.len(), HISTORY_LIMIT);
+        assert_eq!(entry.history[0].version, 3);
+        assert_eq!(entry.value_at(3), Some("v3"));
+        assert_eq!(entry.value_at(current), Some(entry.value.as_str()));
+        assert_eq!(entry.value_at(2), None);
+        assert!(entry.history.iter().all(|v| v.set_at.is_some()));
+    }
+}
@@ -0,0 +1,324 @@
+//! Options shared by every command that opens a vault, resolved once per run
+//! from the command line, the environment and config.toml.
+
+use std::cell::OnceCell;
+use std::fmt;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use ownkey::config::{self, Config, Selection};
+use ownkey::error::{io_context, OwnkeyError, Result};
+use ownkey::vault::{warn_if_insecure_cli_password, PasswordOptions};
+use ownkey::vault_store;
+
+use crate::cli::GlobalArgs;
+
+pub const ENV_VAULT: &str = "OWNKEY_VAULT";
+pub const ENV_PASSWORD_FILE: &str = "OWNKEY_PASSWORD_FILE";
+pub const ENV_NO_SESSION: &str = "OWNKEY_NO_SESSION";
+pub const ENV_KEYCHAIN_ACCOUNT: &str = "OWNKEY_KEYCHAIN_ACCOUNT";
+
+const DEFAULT_KEYCHAIN_SERVICE: &str = "ownkey";
+
+/// Where an effective value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A command-line option such as `--path`.
+    Flag(&'static str),
+    /// An environment variable.
+    Env(&'static str),
+    /// A top-level key in config.toml.
+    Config(&'static str),
+    /// A key in the `[vaults.NAME]` table of the vault in use.
+    Vault(String, &'static str),
+    /// A `.ownkey` marker file.
+    Marker(PathBuf),
+    /// Nothing set it.
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Flag(flag) => write!(f, "{flag}"),
+            Source::Env(name) => write!(f, "${name}"),
+            Source::Config(key) => write!(f, "config.toml {key}"),
+            Source::Vault(name, key) => write!(f, "config.toml vaults.{name}.{key}"),
+            Source::Marker(path) => write!(f, "{}", path.display()),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// A value together with its [`Source`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T, source: Source) -> Self {
+        Setting { value, source }
+    }
+}
+
+/// How the vault password is given, if not through the keychain or a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Password {
+    Literal(String),
+    File(String),
+}
+
+/// The effective global options for this run.
+#[derive(Debug)]
+pub struct Options {
+    /// The named vault in use, if any.
+    pub vault: Option<Setting<String>>,
+    pub path: Setting<String>,
+    pub password: Option<Setting<Password>>,
+    pub keychain_account: Option<Setting<String>>,
+    pub keychain_service: Setting<String>,
+    pub no_session: Setting<bool>,
+    /// Password read from `password`, on first use.
+    loaded: OnceCell<Option<String>>,
+}
+
+impl Options {
+    /// Resolve each option from, in order: its command-line flag, its
+    /// environment variable, the `[vaults.NAME]` table of the vault in use
+    /// (see [`config::selected`]), the top-level config key, and the built-in
+    /// default.
+    pub fn resolve(args: &GlobalArgs, cfg: &Config, env: impl Fn(&str) -> Option<String>) -> Result<Options> {
+        let selected = config::selected();
+        let vault = selected.map(|selected| {
+            let source = match selected.source {
+                Selection::Flag => Source::Flag("--vault"),
+                Selection::Env => Source::Env(ENV_VAULT),
+                Selection::Marker(ref marker) => Source::Marker(marker.clone()),
+                Selection::Default => Source::Config("default_vault"),
+            };
+            Setting::new(selected.name.clone(), source)
+        });
+
+        let path = match (&args.path, selected) {
+            (Some(path), _) => Setting::new(path.clone(), Source::Flag("--path")),
+            (None, Some(selected)) => Setting::new(
+                selected.settings.path.clone(),
+                Source::Vault(selected.name.clone(), "path"),
+            ),
+            (None, None) => Setting::new(vault_store::default_vault_path()?, Source::Default),
+        };
+        // Settings of a named vault only apply to its own file.
+        let in_use = selected.filter(|selected| selected.settings.path == path.value);
+
+        let password = if let Some(ref password) = args.password {
+            Some(Setting::new(Password::Literal(password.clone()), Source::Flag("--password")))
+        } else {
+            pick(
+                args.password_file.as_ref(),
+                "--password-file",
+                env(ENV_PASSWORD_FILE),
+                ENV_PASSWORD_FILE,
+                in_use.map(|selected| (&selected.name, selected.settings.password_file.as_ref())),
+                cfg.password_file.as_ref(),
+                "password_file",
+            )
+            .map(|setting| Setting::new(Password::File(setting.value), setting.source))
+        };
+        let keychain_account = pick(
+            args.keychain_account.as_ref(),
+            "--keychain-account",
+            env(ENV_KEYCHAIN_ACCOUNT),
+            ENV_KEYCHAIN_ACCOUNT,
+            in_use.map(|selected| (&selected.name, selected.settings.keychain_account.as_ref())),
+            cfg.keychain_account.as_ref(),
+            "keychain_account",
+        );
+        let keychain_service = match (&args.keychain_service, &cfg.keychain_service) {
+            (Some(service), _) => Setting::new(service.clone(), Source::Flag("--keychain-service")),
+            (None, Some(service)) => Setting::new(service.clone(), Source::Config("keychain_service")),
+            (None, None) => Setting::new(DEFAULT_KEYCHAIN_SERVICE.to_string(), Source::Default),
+        };
+        let no_session = if args.no_session {
+            Setting::new(true, Source::Flag("--no-session"))
+        } else if let Some(value) = env(ENV_NO_SESSION) {
+            Setting::new(parse_bool(ENV_NO_SESSION, &value)?, Source::Env(ENV_NO_SESSION))
+        } else if let Some(value) = cfg.no_session {
+            Setting::new(value, Source::Config("no_session"))
+        } else {
+            Setting::new(false, Source::Default)
+        };
+
+        Ok(Options {
+            vault,
+            path,
+            password,
+            keychain_account,
+            keychain_service,
+            no_session,
+            loaded: OnceCell::new(),
+        })
+    }
+
+    /// The vault file to use.
+    pub fn vault_path(&self) -> &str {
+        &self.path.value
+    }
+
+    /// Password options for opening the vault at `vault_path`, normally
+    /// [`Options::vault_path`]. A password file is read on the first call.
+    pub fn password_options<'a>(&'a self, vault_path: &'a str) -> Result<PasswordOptions<'a>> {
+        if self.loaded.get().is_none() {
+            let password = match self.password {
+                Some(Setting { value: Password::Literal(ref password), .. }) => {
+                    warn_if_insecure_cli_password(&PasswordOptions::new(Some(password), vault_path));
+                    Some(password.clone())
+                }
+                Some(Setting { value: Password::File(ref file), .. }) => Some(read_password_file(file)?),
+                None => None,
+            };
+            let _ = self.loaded.set(password);
+        }
+        let password = self
+            .password
+            .as_ref()
+            .filter(|setting| self.applies(setting, vault_path))
+            .and_then(|_| self.loaded.get().and_then(Option::as_deref));
+        Ok(PasswordOptions {
+            password,
+            keychain_account: self
+                .keychain_account
+                .as_ref()
+                .filter(|setting| self.applies(setting, vault_path))
+                .map(|setting| setting.value.as_str()),
+            keychain_service: &self.keychain_service.value,
+            vault_path,
+            no_session: self.no_session.value,
+        })
+    }
+
+    /// Settings of the named vault in use do not carry over to another file.
+    fn applies<T>(&self, setting: &Setting<T>, vault_path: &str) -> bool {
+        vault_path == self.path.value || !matches!(setting.source, Source::Vault(..))
+    }
+}
+
+/// The first of flag, environment variable, vault setting and config key
+/// that is set.
+fn pick(
+    flag: Option<&String>,
+    flag_name: &'static str,
+    env: Option<String>,
+    env_name: &'static str,
+    vault: Option<(&String, Option<&String>)>,
+    config: Option<&String>,
+    key: &'static str,
+) -> Option<Setting<String>> {
+    if let Some(value) = flag {
+        return Some(Setting::new(value.clone(), Source::Flag(flag_name)));
+    }
+    if let Some(value) = env {
+        return Some(Setting::new(value, Source::Env(env_name)));
+    }
+    if let Some((name, Some(value))) = vault {
+        return Some(Setting::new(value.clone(), Source::Vault(name.clone(), key)));
+    }
+    config.map(|value| Setting::new(value.clone(), Source::Config(key)))
+}
+
+/// Read the value of an environment variable; empty counts as unset.
+pub fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(OwnkeyError::InvalidInput(format!(
+            "invalid {name} value '{value}': use 1 or 0"
+        ))),
+    }
+}
+
+/// The password is the file's first line, without its line ending.
+fn read_password_file(file: &str) -> Result<String> {
+    let contents =
+        fs::read_to_string(file).map_err(io_context(format!("failed to read password file {file}")))?;
+    #[cfg(unix)]
+    {
+        if let Ok(meta) = fs::metadata(file) {
+            if meta.permissions().mode() & 0o077 != 0 {
+                eprintln!("Warning: password file {file} is readable by other users; consider `chmod 600 {file}`.");
+            }
+        }
+    }
+    let password = contents.lines().next().unwrap_or_default();
+    if password.is_empty() {
+        return Err(OwnkeyError::InvalidInput(format!("password file {file} is empty")));
+    }
+    Ok(password.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(args: &GlobalArgs, cfg: &str, env: &[(&str, &str)]) -> Result<Options> {
+        let cfg: Config = toml::from_str(cfg).unwrap();
+        Options::resolve(args, &cfg, |name| {
+            env.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn flags_win_over_environment_and_config() {
+        let cfg = "keychain_account = \"cfg\"\nkeychain_service = \"svc\"\nno_session = true\npassword_file = \"/cfg/pw\"";
+        let args = GlobalArgs { path: Some("/tmp/v.json".into()), ..GlobalArgs::default() };
+
+        let options = resolve(&args, cfg, &[]).unwrap();
+        assert_eq!(options.path, Setting::new("/tmp/v.json".to_string(), Source::Flag("--path")));
+        assert_eq!(options.keychain_account, Some(Setting::new("cfg".into(), Source::Config("keychain_account"))));
+        assert_eq!(options.keychain_service.value, "svc");
+        assert_eq!(options.no_session, Setting::new(true, Source::Config("no_session")));
+        assert_eq!(options.password.unwrap().value, Password::File("/cfg/pw".into()));
+
+        let env = [(ENV_KEYCHAIN_ACCOUNT, "env"), (ENV_NO_SESSION, "0"), (ENV_PASSWORD_FILE, "/env/pw")];
+        let options = resolve(&args, cfg, &env).unwrap();
+        assert_eq!(options.keychain_account.unwrap().source, Source::Env(ENV_KEYCHAIN_ACCOUNT));
+        assert_eq!(options.no_session, Setting::new(false, Source::Env(ENV_NO_SESSION)));
+        assert_eq!(options.password.unwrap().value, Password::File("/env/pw".into()));
+
+        let args = GlobalArgs {
+            keychain_account: Some("flag".into()),
+            no_session: true,
+            password: Some("pw".into()),
+            ..args
+        };
+        let options = resolve(&args, cfg, &env).unwrap();
+        assert_eq!(options.keychain_account.unwrap().value, "flag");
+        assert_eq!(options.no_session.source, Source::Flag("--no-session"));
+        assert_eq!(options.password.unwrap().source, Source::Flag("--password"));
+        assert!(resolve(&GlobalArgs { path: Some("/v".into()), ..GlobalArgs::default() }, "", &[(ENV_NO_SESSION, "maybe")]).is_err());
+    }
+
+    #[test]
+    fn password_files_give_their_first_line() {
+        let file = std::env::temp_dir().join(format!("ownkey_pw_{}", uuid::Uuid::new_v4()));
+        fs::write(&file, "s3cret\r\nignored\n").unwrap();
+        let args = GlobalArgs {
+            path: Some("/tmp/v.json".into()),
+            password_file: Some(file.to_string_lossy().into_owned()),
+            ..GlobalArgs::default()
+        };
+        let options = resolve(&args, "", &[]).unwrap();
+        assert_eq!(options.password_options("/tmp/v.json").unwrap().password, Some("s3cret"));
+
+        fs::write(&file, "\n").unwrap();
+        let options = resolve(&args, "", &[]).unwrap();
+        assert!(options.password_options("/tmp/v.json").is_err());
+    }
+}
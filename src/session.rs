@@ -2,11 +2,12 @@ use std::fs;
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use anyhow::{Context, Result};
 use base64::{engine::general_purpose, Engine as _};
 use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
 
+use crate::error::{io_context, OwnkeyError, Result};
+
 const SESSION_TTL: Duration = Duration::from_secs(300);
 
 #[derive(Serialize, Deserialize)]
@@ -17,9 +18,9 @@ struct SessionData {
 }
 
 fn session_path() -> Result<PathBuf> {
-    let base = BaseDirs::new().ok_or_else(|| anyhow::anyhow!("cannot resolve home directory"))?;
+    let base = BaseDirs::new().ok_or(OwnkeyError::NoHomeDir)?;
     let dir = base.home_dir().join(".ownkey");
-    fs::create_dir_all(&dir)?;
+    fs::create_dir_all(&dir).map_err(io_context(format!("failed to create {}", dir.display())))?;
     Ok(dir.join("session"))
 }
 
@@ -28,9 +29,9 @@ pub fn load(vault_path: &str) -> Result<Option<[u8; 32]>> {
     if !path.exists() {
         return Ok(None);
     }
-    let contents = fs::read_to_string(&path).with_context(|| "failed to read session cache")?;
-    let data: SessionData =
-        serde_json::from_str(&contents).with_context(|| "failed to parse session cache")?;
+    let contents = fs::read_to_string(&path).map_err(io_context("failed to read session cache"))?;
+    let data: SessionData = serde_json::from_str(&contents)
+        .map_err(|e| OwnkeyError::Corrupt(format!("failed to parse session cache: {e}")))?;
 
     if data.vault_path != vault_path {
         return Ok(None);
@@ -46,7 +47,7 @@ pub fn load(vault_path: &str) -> Result<Option<[u8; 32]>> {
 
     let bytes = general_purpose::STANDARD
         .decode(&data.key_b64)
-        .with_context(|| "invalid session key encoding")?;
+        .map_err(|_| OwnkeyError::Corrupt("invalid session key encoding".into()))?;
     let mut key = [0u8; 32];
     if bytes.len() == 32 {
         key.copy_from_slice(&bytes);
@@ -62,14 +63,18 @@ pub fn store(vault_path: &str, key: &[u8; 32]) -> Result<()> {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_else(|_| Duration::from_secs(0))
         .as_secs();
-    let expires_at = now + SESSION_TTL.as_secs();
+    let ttl = crate::config::settings_for(vault_path)
+        .and_then(|settings| settings.session_ttl)
+        .unwrap_or(SESSION_TTL);
+    let expires_at = now + ttl.as_secs();
 
     let data = SessionData {
         vault_path: vault_path.to_string(),
         key_b64: general_purpose::STANDARD.encode(key),
         expires_at,
     };
-    let json = serde_json::to_string(&data)?;
-    fs::write(path, json).with_context(|| "failed to write session cache")?;
+    let json = serde_json::to_string(&data)
+        .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize session cache: {e}")))?;
+    fs::write(path, json).map_err(io_context("failed to write session cache"))?;
     Ok(())
 }
@@ -2,7 +2,6 @@ use std::path::Path;
 
 use aes_gcm::aead::{Aead, KeyInit, OsRng};
 use aes_gcm::{Aes256Gcm, Nonce};
-use anyhow::{Context, Result};
 use base64::{engine::general_purpose, Engine as _};
 use pbkdf2::pbkdf2_hmac;
 use rand::RngCore;
@@ -10,6 +9,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{self, Value};
 use sha2::Sha256;
 
+use crate::error::{io_context, OwnkeyError, Result};
 use crate::keychain;
 use crate::models::Vault;
 use crate::session;
@@ -36,7 +36,6 @@ Prefer leaving --password empty and entering it interactively when prompted."
 impl<'a> PasswordOptions<'a> {
     /// Create a new PasswordOptions with minimal configuration.
     /// This is a convenience constructor for simple use cases.
-    #[allow(dead_code)]
     pub fn new(password: Option<&'a str>, vault_path: &'a str) -> Self {
         Self {
             password,
@@ -48,60 +47,121 @@ impl<'a> PasswordOptions<'a> {
     }
 }
 
+/// Key material derived from the vault password.
+///
+/// The KDF salt travels with the derived key so that a vault can be
+/// re-encrypted on save without prompting again or re-running PBKDF2.
+#[derive(Clone)]
+pub struct VaultKey {
+    bytes: [u8; 32],
+    salt: Vec<u8>,
+}
+
+impl VaultKey {
+    /// Derive the key for an existing vault from its password and stored salt.
+    pub fn derive(password: &str, salt: &[u8]) -> Self {
+        Self {
+            bytes: derive_key_bytes(password, salt),
+            salt: salt.to_vec(),
+        }
+    }
+
+    /// Derive a key under a freshly generated salt (new vaults and password rotation).
+    pub fn generate(password: &str) -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive(password, &salt)
+    }
+
+    fn from_parts(bytes: [u8; 32], salt: Vec<u8>) -> Self {
+        Self { bytes, salt }
+    }
+}
+
+/// A decrypted vault together with the key it was unlocked with.
+///
+/// Saving an `UnlockedVault` reuses that key, so a command that loaded the
+/// vault from the session cache never prompts for the password again.
+pub struct UnlockedVault {
+    pub vault: Vault,
+    key: Option<VaultKey>,
+}
+
+impl UnlockedVault {
+    pub fn new(vault: Vault, key: VaultKey) -> Self {
+        Self {
+            vault,
+            key: Some(key),
+        }
+    }
+
+    /// Whether a key is available, i.e. the vault was read from an encrypted file.
+    pub fn has_key(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// The raw vault key, for deriving or protecting other files with it.
+    pub(crate) fn key_bytes(&self) -> Option<&[u8; 32]> {
+        self.key.as_ref().map(|key| &key.bytes)
+    }
+
+    /// Replace the key with one derived from `password` under a fresh salt.
+    pub fn rekey(&mut self, password: &str) {
+        self.key = Some(VaultKey::generate(password));
+    }
+}
+
 /// Convenience wrapper for load_vault_with_password with default options.
-/// Reserved for library usage or simpler scripting scenarios.
-#[allow(dead_code)]
-pub fn load_vault(path: &str) -> Result<Vault> {
+/// Intended for library usage or simpler scripting scenarios.
+pub fn load_vault(path: &str) -> Result<UnlockedVault> {
     load_vault_with_password(path, &PasswordOptions::new(None, path))
 }
 
 /// Convenience wrapper for save_vault_with_password with default options.
-/// Reserved for library usage or simpler scripting scenarios.
-#[allow(dead_code)]
-pub fn save_vault(path: &str, vault: &Vault) -> Result<()> {
-    save_vault_with_password(path, vault, &PasswordOptions::new(None, path))
+/// Intended for library usage or simpler scripting scenarios.
+pub fn save_vault(path: &str, unlocked: &mut UnlockedVault) -> Result<()> {
+    save_vault_with_password(path, unlocked, &PasswordOptions::new(None, path))
 }
 
 /// Convenience wrapper for ensure_vault_exists_with_password with default options.
-/// Reserved for library usage or simpler scripting scenarios.
-#[allow(dead_code)]
+/// Intended for library usage or simpler scripting scenarios.
 pub fn ensure_vault_exists(path: &str) -> Result<()> {
     ensure_vault_exists_with_password(path, &PasswordOptions::new(None, path))
 }
 
-pub fn load_vault_with_password(path: &str, opts: &PasswordOptions<'_>) -> Result<Vault> {
-    let contents = vault_store::lock_and_read(Path::new(path))
-        .with_context(|| "Vault not found. Run `ownkey init` to create a new encrypted vault.")?;
+pub fn load_vault_with_password(path: &str, opts: &PasswordOptions<'_>) -> Result<UnlockedVault> {
+    let contents = vault_store::lock_and_read(Path::new(path))?;
 
     if let Ok(blob) = serde_json::from_str::<EncryptedVault>(&contents) {
+        let salt = general_purpose::STANDARD
+            .decode(&blob.salt)
+            .map_err(|_| invalid_format())?;
+
         if !opts.no_session {
             if let Ok(Some(key_bytes)) = session::load(opts.vault_path) {
                 if let Ok(vault) = decrypt_vault_with_key(&blob, &key_bytes) {
-                    return Ok(vault);
+                    return Ok(UnlockedVault::new(vault, VaultKey::from_parts(key_bytes, salt)));
                 }
             }
         }
 
         let pass = get_password(opts)?;
-        let salt = general_purpose::STANDARD
-            .decode(&blob.salt)
-            .with_context(|| "Vault format invalid. Please restore from backup or reinitialize.")?;
-        let key_bytes = derive_key_bytes(&pass, &salt);
+        let key = VaultKey::derive(&pass, &salt);
+        let vault = decrypt_vault_with_key(&blob, &key.bytes)?;
         if !opts.no_session {
-            let _ = session::store(opts.vault_path, &key_bytes);
+            let _ = session::store(opts.vault_path, &key.bytes);
         }
-        return decrypt_vault_with_key(&blob, &key_bytes)
-            .with_context(|| "Vault password is incorrect or vault is corrupted.");
+        return Ok(UnlockedVault::new(vault, key));
     }
 
-    // Fallback: best-effort compatibility for old plain JSON.
+    // Fallback: best-effort compatibility for old plain JSON. These vaults have
+    // no key yet; one is derived the first time they are saved.
     match serde_json::from_str::<Vault>(&contents) {
-        Ok(vault) => Ok(vault),
-        Err(primary_err) => {
-            let value: Value = serde_json::from_str(&contents)
-                .with_context(|| "failed to parse vault JSON for migration")?;
+        Ok(vault) => Ok(UnlockedVault { vault, key: None }),
+        Err(_) => {
+            let value: Value = serde_json::from_str(&contents).map_err(|_| damaged())?;
             if let Some(items) = value.get("items").and_then(|v| v.as_array()) {
-                let mut entries = std::collections::HashMap::new();
+                let mut entries = std::collections::BTreeMap::new();
                 for item in items {
                     if let Some(name) = item.get("name").and_then(|n| n.as_str()) {
                         let secret = item
@@ -109,28 +169,39 @@ pub fn load_vault_with_password(path: &str, opts: &PasswordOptions<'_>) -> Resul
                             .and_then(|s| s.as_str())
                             .unwrap_or_default()
                             .to_string();
-                        entries.insert(name.to_string(), secret);
+                        entries.insert(name.to_string(), secret.into());
                     }
                 }
-                return Ok(Vault { entries });
+                return Ok(UnlockedVault {
+                    vault: Vault { entries },
+                    key: None,
+                });
             }
-            Err(primary_err).with_context(|| "Vault file appears damaged or truncated. A backup copy may be available.")
+            Err(damaged())
         }
     }
 }
 
-pub fn save_vault_with_password(path: &str, vault: &Vault, opts: &PasswordOptions<'_>) -> Result<()> {
-    let pass = get_password(opts)?;
-    let encrypted = encrypt_vault(vault, &pass)?;
+/// Encrypt and write `unlocked` back to `path`.
+///
+/// The key the vault was unlocked with is reused, so this only asks for a
+/// password when the vault was loaded from a legacy plaintext file.
+pub fn save_vault_with_password(
+    path: &str,
+    unlocked: &mut UnlockedVault,
+    opts: &PasswordOptions<'_>,
+) -> Result<()> {
+    let key = match unlocked.key.take() {
+        Some(key) => key,
+        None => VaultKey::generate(&get_password(opts)?),
+    };
+    let encrypted = encrypt_vault(&unlocked.vault, &key)?;
     let json = serde_json::to_string_pretty(&encrypted)
-        .with_context(|| "failed to serialize encrypted vault")?;
-    let salt_bytes = general_purpose::STANDARD
-        .decode(&encrypted.salt)
-        .with_context(|| "Vault format invalid. Please restore from backup or reinitialize.")?;
+        .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize encrypted vault: {e}")))?;
     if !opts.no_session {
-        let key_bytes = derive_key_bytes(&pass, &salt_bytes);
-        let _ = session::store(opts.vault_path, &key_bytes);
+        let _ = session::store(opts.vault_path, &key.bytes);
     }
+    unlocked.key = Some(key);
     vault_store::lock_and_write(Path::new(path), &json)?;
     Ok(())
 }
@@ -144,26 +215,17 @@ pub fn ensure_vault_exists_with_password(path: &str, opts: &PasswordOptions<'_>)
 
     if let Some(parent) = vault_path.parent() {
         if !parent.as_os_str().is_empty() {
-            std::fs::create_dir_all(parent)?;
+            std::fs::create_dir_all(parent)
+                .map_err(io_context(format!("failed to create {}", parent.display())))?;
         }
     }
 
-    let empty_vault = Vault::default();
     let pass = match opts.password {
         Some(p) => p.to_string(),
         None => prompt_new_password()?,
     };
-    save_vault_with_password(
-        path,
-        &empty_vault,
-        &PasswordOptions {
-            password: Some(&pass),
-            keychain_account: opts.keychain_account,
-            keychain_service: opts.keychain_service,
-            vault_path: opts.vault_path,
-            no_session: opts.no_session,
-        },
-    )?;
+    let mut unlocked = UnlockedVault::new(Vault::default(), VaultKey::generate(&pass));
+    save_vault_with_password(path, &mut unlocked, opts)?;
     if let Some(account) = opts.keychain_account {
         let _ = keychain::store_password(opts.keychain_service, account, &pass);
     } else {
@@ -177,8 +239,9 @@ pub fn ensure_vault_exists_with_password(path: &str, opts: &PasswordOptions<'_>)
     Ok(())
 }
 
+/// On-disk representation of an encrypted vault.
 #[derive(Serialize, Deserialize)]
-struct EncryptedVault {
+pub struct EncryptedVault {
     salt: String,
     nonce: String,
     ciphertext: String,
@@ -205,39 +268,38 @@ fn get_password(opts: &PasswordOptions<'_>) -> Result<String> {
             }
         }
     }
-    let password =
-        rpassword::prompt_password("Enter vault password: ").with_context(|| "password prompt")?;
+    let password = rpassword::prompt_password("Enter vault password: ")
+        .map_err(io_context("password prompt"))?;
     Ok(password)
 }
 
 pub fn prompt_new_password() -> Result<String> {
     let first = rpassword::prompt_password("Set a new vault password: ")
-        .with_context(|| "password prompt")?;
+        .map_err(io_context("password prompt"))?;
     let second = rpassword::prompt_password("Confirm password: ")
-        .with_context(|| "password confirm prompt")?;
+        .map_err(io_context("password confirm prompt"))?;
     if first != second {
-        anyhow::bail!("passwords do not match");
+        return Err(OwnkeyError::PasswordMismatch);
     }
     Ok(first)
 }
 
-fn encrypt_vault(vault: &Vault, password: &str) -> Result<EncryptedVault> {
-    let mut salt = [0u8; 16];
-    OsRng.fill_bytes(&mut salt);
-    let key = derive_key(password, &salt);
-
+/// Encrypt `vault` under `key` with a fresh nonce. The result serializes to
+/// the same JSON format as a vault file, so it can be opened with `--path`.
+pub fn encrypt_vault(vault: &Vault, key: &VaultKey) -> Result<EncryptedVault> {
     let mut nonce_bytes = [0u8; 12];
     OsRng.fill_bytes(&mut nonce_bytes);
     let nonce = Nonce::from_slice(&nonce_bytes);
 
-    let cipher = Aes256Gcm::new(&key);
-    let plaintext = serde_json::to_vec(vault).with_context(|| "failed to serialize vault")?;
+    let cipher = Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(&key.bytes));
+    let plaintext = serde_json::to_vec(vault)
+        .map_err(|e| OwnkeyError::Crypto(format!("failed to serialize vault: {e}")))?;
     let ciphertext = cipher
         .encrypt(nonce, plaintext.as_ref())
-        .map_err(|e| anyhow::anyhow!("encryption failed: {e:?}"))?;
+        .map_err(|e| OwnkeyError::Crypto(format!("{e:?}")))?;
 
     Ok(EncryptedVault {
-        salt: general_purpose::STANDARD.encode(salt),
+        salt: general_purpose::STANDARD.encode(&key.salt),
         nonce: general_purpose::STANDARD.encode(nonce_bytes),
         ciphertext: general_purpose::STANDARD.encode(ciphertext),
     })
@@ -246,23 +308,32 @@ fn encrypt_vault(vault: &Vault, password: &str) -> Result<EncryptedVault> {
 fn decrypt_vault_with_key(blob: &EncryptedVault, key_bytes: &[u8; 32]) -> Result<Vault> {
     let nonce_bytes = general_purpose::STANDARD
         .decode(&blob.nonce)
-        .with_context(|| "invalid nonce encoding")?;
+        .map_err(|_| OwnkeyError::Corrupt("invalid nonce encoding".into()))?;
     let ciphertext = general_purpose::STANDARD
         .decode(&blob.ciphertext)
-        .with_context(|| "invalid ciphertext encoding")?;
-    let cipher = Aes256Gcm::new_from_slice(key_bytes).map_err(|e| anyhow::anyhow!(e))?;
+        .map_err(|_| OwnkeyError::Corrupt("invalid ciphertext encoding".into()))?;
+    if nonce_bytes.len() != 12 {
+        return Err(OwnkeyError::Corrupt("invalid nonce length".into()));
+    }
+    let cipher = Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(key_bytes));
     let nonce = Nonce::from_slice(&nonce_bytes);
     let plaintext = cipher
         .decrypt(nonce, ciphertext.as_ref())
-        .map_err(|_| anyhow::anyhow!("Vault password is incorrect or vault is corrupted."))?;
-    let vault: Vault =
-        serde_json::from_slice(&plaintext).with_context(|| "Vault format invalid. Please restore from backup or reinitialize.")?;
+        .map_err(|_| OwnkeyError::WrongPassword)?;
+    let vault: Vault = serde_json::from_slice(&plaintext).map_err(|_| invalid_format())?;
     Ok(vault)
 }
 
-fn derive_key(password: &str, salt: &[u8]) -> aes_gcm::Key<Aes256Gcm> {
-    let key = derive_key_bytes(password, salt);
-    aes_gcm::Key::<Aes256Gcm>::from_slice(&key).to_owned()
+fn invalid_format() -> OwnkeyError {
+    OwnkeyError::Corrupt(
+        "Vault format invalid. Please restore from backup or reinitialize.".into(),
+    )
+}
+
+fn damaged() -> OwnkeyError {
+    OwnkeyError::Corrupt(
+        "Vault file appears damaged or truncated. A backup copy may be available.".into(),
+    )
 }
 
 fn derive_key_bytes(password: &str, salt: &[u8]) -> [u8; 32] {
@@ -274,7 +345,7 @@ fn derive_key_bytes(password: &str, salt: &[u8]) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use std::collections::BTreeMap;
     use std::fs;
     use std::path::PathBuf;
 
@@ -286,13 +357,14 @@ mod tests {
 
     #[test]
     fn encrypt_decrypt_round_trip_works() {
-        let mut entries = HashMap::new();
-        entries.insert("key1".to_string(), "secret1".to_string());
-        entries.insert("key2".to_string(), "secret2".to_string());
+        let mut entries = BTreeMap::new();
+        entries.insert("key1".to_string(), "secret1".into());
+        entries.insert("key2".to_string(), "secret2".into());
         let vault = Vault { entries: entries.clone() };
 
         let password = "testpw";
-        let encrypted = encrypt_vault(&vault, password).expect("encrypt_vault should succeed");
+        let encrypted = encrypt_vault(&vault, &VaultKey::generate(password))
+            .expect("encrypt_vault should succeed");
         assert!(!encrypted.ciphertext.is_empty(), "ciphertext should not be empty");
 
         let salt_bytes = general_purpose::STANDARD
@@ -307,12 +379,12 @@ mod tests {
 
     #[test]
     fn decrypt_with_wrong_password_fails() {
-        let mut entries = HashMap::new();
-        entries.insert("key".to_string(), "secret".to_string());
+        let mut entries = BTreeMap::new();
+        entries.insert("key".to_string(), "secret".into());
         let vault = Vault { entries };
 
-        let encrypted =
-            encrypt_vault(&vault, "correct_pw").expect("encrypt_vault should succeed with correct_pw");
+        let encrypted = encrypt_vault(&vault, &VaultKey::generate("correct_pw"))
+            .expect("encrypt_vault should succeed with correct_pw");
         let salt_bytes = general_purpose::STANDARD
             .decode(&encrypted.salt)
             .expect("salt should be valid base64");
@@ -324,11 +396,12 @@ mod tests {
 
     #[test]
     fn corrupt_ciphertext_cannot_be_decrypted() {
-        let mut entries = HashMap::new();
-        entries.insert("key".to_string(), "secret".to_string());
+        let mut entries = BTreeMap::new();
+        entries.insert("key".to_string(), "secret".into());
         let vault = Vault { entries };
 
-        let encrypted = encrypt_vault(&vault, "pw").expect("encrypt_vault should succeed");
+        let encrypted =
+            encrypt_vault(&vault, &VaultKey::generate("pw")).expect("encrypt_vault should succeed");
 
         // Corrupt the ciphertext by truncating it.
         let corrupted = EncryptedVault {
@@ -370,4 +443,42 @@ mod tests {
 
         let _ = fs::remove_file(&path);
     }
+
+    #[test]
+    fn save_reuses_key_and_salt_from_load() {
+        let path = temp_vault_path("reuse_key");
+        let path_str = path.to_string_lossy().to_string();
+        let _ = fs::remove_file(&path);
+
+        let opts = PasswordOptions {
+            password: Some("testpw"),
+            keychain_account: None,
+            keychain_service: "ownkey",
+            vault_path: &path_str,
+            no_session: true,
+        };
+        ensure_vault_exists_with_password(&path_str, &opts).expect("vault should be created");
+        let first: EncryptedVault =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        // Saving must not need the password once the vault is unlocked.
+        let no_password = PasswordOptions {
+            password: None,
+            ..opts.clone()
+        };
+        let mut unlocked = load_vault_with_password(&path_str, &opts).expect("load should succeed");
+        unlocked.vault.entries.insert("k".to_string(), "v".into());
+        save_vault_with_password(&path_str, &mut unlocked, &no_password)
+            .expect("save should reuse the unlocked key");
+
+        let second: EncryptedVault =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(first.salt, second.salt, "salt should be reused across saves");
+        assert_ne!(first.nonce, second.nonce, "nonce must be fresh for every save");
+
+        let reloaded = load_vault_with_password(&path_str, &opts).expect("reload should succeed");
+        assert_eq!(reloaded.vault.entries.get("k").map(|e| e.value.as_str()), Some("v"));
+
+        let _ = fs::remove_file(&path);
+    }
 }
@@ -3,53 +3,77 @@ use std::io::{Read, Write};
 #[cfg(unix)]
 use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use anyhow::{anyhow, Context, Result};
 use directories::BaseDirs;
 use fd_lock::RwLock;
 use rand::RngCore;
 
+use crate::config;
+use crate::error::{io_context, OwnkeyError, Result};
+
 const TMP_SUFFIX: &str = ".tmp";
+/// How long to wait for another ownkey process to release the vault.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
 
+/// The vault to use when no `--path` is given: the selected named vault
+/// (see [`config::selected`]), else `~/.ownkey/vault.json`.
 pub fn default_vault_path() -> Result<String> {
-    let base = BaseDirs::new().ok_or_else(|| anyhow!("cannot resolve home directory"))?;
+    if let Some(selected) = config::selected() {
+        return Ok(selected.settings.path.clone());
+    }
+    let base = BaseDirs::new().ok_or(OwnkeyError::NoHomeDir)?;
     let dir = base.home_dir().join(".ownkey");
-    fs::create_dir_all(&dir)?;
+    fs::create_dir_all(&dir).map_err(io_context(format!("failed to create {}", dir.display())))?;
     Ok(dir.join("vault.json").to_string_lossy().to_string())
 }
 
-fn backup_path() -> Result<PathBuf> {
-    let base = BaseDirs::new().ok_or_else(|| anyhow!("cannot resolve home directory"))?;
+/// Backup of the vault at `path`: named vaults get their own file, everything
+/// else shares `vault.json.bak`.
+fn backup_path(path: &Path) -> Result<PathBuf> {
+    let base = BaseDirs::new().ok_or(OwnkeyError::NoHomeDir)?;
     let dir = base.home_dir().join(".ownkey").join("backups");
-    fs::create_dir_all(&dir)?;
-    Ok(dir.join("vault.json.bak"))
+    fs::create_dir_all(&dir).map_err(io_context(format!("failed to create {}", dir.display())))?;
+    let name = match config::selected() {
+        Some(selected) if Path::new(&selected.settings.path) == path => {
+            format!("{}.json.bak", selected.name)
+        }
+        _ => "vault.json.bak".to_string(),
+    };
+    Ok(dir.join(name))
 }
 
 pub fn lock_and_read(path: &Path) -> Result<String> {
-    prepare_parent(path)?;
-    let mut open = OpenOptions::new();
-    open.create(true).read(true).write(true);
-    #[cfg(unix)]
-    {
-        open.mode(0o600);
-    }
-    let file = open
-        .open(path)
-        .with_context(|| format!("failed to open vault at {}", path.display()))?;
-    let mut lock = RwLock::new(file);
-    let _guard = lock
-        .write()
-        .map_err(|_| anyhow!("Vault is currently in use by another ownkey process. Please try again later."))?;
-    enforce_permissions(path)?;
-
-    let mut f = OpenOptions::new().read(true).open(path)?;
-    let mut buf = String::new();
-    f.read_to_string(&mut buf)
-        .with_context(|| "failed to read vault file")?;
-    Ok(buf)
+    with_lock(path, || {
+        enforce_permissions(path)?;
+        let mut f = OpenOptions::new()
+            .read(true)
+            .open(path)
+            .map_err(io_context(format!("failed to open vault at {}", path.display())))?;
+        let mut buf = String::new();
+        f.read_to_string(&mut buf)
+            .map_err(io_context("failed to read vault file"))?;
+        Ok(buf)
+    })
 }
 
 pub fn lock_and_write(path: &Path, contents: &str) -> Result<()> {
+    with_lock(path, || {
+        enforce_permissions(path)?;
+        atomic_write(path, contents)?;
+        let backup = backup_path(path)?;
+        atomic_write(&backup, contents)?;
+        Ok(())
+    })
+}
+
+/// Run `f` while holding an exclusive lock on `path` (created with 0600
+/// permissions if missing). Another ownkey process holding the lock is
+/// waited for up to [`LOCK_TIMEOUT`], then this fails with
+/// [`OwnkeyError::Locked`].
+pub fn with_lock<T>(path: &Path, f: impl FnOnce() -> Result<T>) -> Result<T> {
     prepare_parent(path)?;
     let mut open = OpenOptions::new();
     open.create(true).read(true).write(true);
@@ -59,28 +83,38 @@ pub fn lock_and_write(path: &Path, contents: &str) -> Result<()> {
     }
     let file = open
         .open(path)
-        .with_context(|| format!("failed to open vault at {}", path.display()))?;
+        .map_err(io_context(format!("failed to open {}", path.display())))?;
     let mut lock = RwLock::new(file);
-    let _guard = lock
-        .write()
-        .map_err(|_| anyhow!("Vault is currently in use by another ownkey process. Please try again later."))?;
-    enforce_permissions(path)?;
-    atomic_write(path, contents)?;
-    let backup = backup_path()?;
-    atomic_write(&backup, contents)?;
-    Ok(())
+    let deadline = Instant::now() + LOCK_TIMEOUT;
+    loop {
+        match lock.try_write() {
+            Ok(_guard) => return f(),
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock && Instant::now() < deadline => {
+                thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(err) => return Err(lock_error(err)),
+        }
+    }
+}
+
+fn lock_error(err: std::io::Error) -> OwnkeyError {
+    match err.kind() {
+        std::io::ErrorKind::WouldBlock => OwnkeyError::Locked,
+        _ => io_context("failed to lock vault")(err),
+    }
 }
 
 fn prepare_parent(path: &Path) -> Result<()> {
     if let Some(parent) = path.parent() {
         if !parent.as_os_str().is_empty() {
-            fs::create_dir_all(parent)?;
+            fs::create_dir_all(parent)
+                .map_err(io_context(format!("failed to create {}", parent.display())))?;
         }
     }
     Ok(())
 }
 
-pub fn atomic_write(path: &Path, contents: &str) -> Result<()> {
+pub fn atomic_write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
     let mut rng = rand::thread_rng();
     let mut suffix_bytes = [0u8; 8];
     rng.fill_bytes(&mut suffix_bytes);
@@ -104,14 +138,14 @@ pub fn atomic_write(path: &Path, contents: &str) -> Result<()> {
         }
         let mut f = open
             .open(&tmp_path)
-            .with_context(|| format!("failed to open temp file {}", tmp_path.display()))?;
-        f.write_all(contents.as_bytes())
-            .with_context(|| "failed to write temp vault")?;
-        f.sync_all().with_context(|| "failed to sync temp vault")?;
+            .map_err(io_context(format!("failed to open temp file {}", tmp_path.display())))?;
+        f.write_all(contents.as_ref())
+            .map_err(io_context("failed to write temp vault"))?;
+        f.sync_all().map_err(io_context("failed to sync temp vault"))?;
     }
 
     fs::rename(&tmp_path, path)
-        .with_context(|| format!("failed to replace vault at {}", path.display()))?;
+        .map_err(io_context(format!("failed to replace vault at {}", path.display())))?;
 
     if let Some(parent) = path.parent() {
         if let Ok(dir) = OpenOptions::new().read(true).open(parent) {
@@ -126,12 +160,14 @@ pub fn enforce_permissions(path: &Path) -> Result<()> {
     #[cfg(unix)]
     {
         use std::fs::Permissions;
-        let metadata = fs::metadata(path)?;
+        let metadata = fs::metadata(path)
+            .map_err(io_context(format!("failed to read metadata for {}", path.display())))?;
         let mode = metadata.permissions().mode() & 0o777;
         if mode != 0o600 {
             eprintln!("Warning: vault file permissions are insecure (expected 600). Fixing...");
             let perm = Permissions::from_mode(0o600);
-            fs::set_permissions(path, perm)?;
+            fs::set_permissions(path, perm)
+                .map_err(io_context(format!("failed to set permissions on {}", path.display())))?;
         }
     }
     #[cfg(windows)]
@@ -143,12 +179,12 @@ pub fn enforce_permissions(path: &Path) -> Result<()> {
 
 pub fn restore_backup() -> Result<()> {
     let path = PathBuf::from(default_vault_path()?);
-    let backup = backup_path()?;
+    let backup = backup_path(&path)?;
     if !backup.exists() {
-        return Err(anyhow!("No backup found at {}", backup.display()));
+        return Err(OwnkeyError::NoBackup(backup.display().to_string()));
     }
     let contents = fs::read_to_string(&backup)
-        .with_context(|| format!("failed to read backup {}", backup.display()))?;
+        .map_err(io_context(format!("failed to read backup {}", backup.display())))?;
     atomic_write(&path, &contents)?;
     Ok(())
 }
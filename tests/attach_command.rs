@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use uuid::Uuid;
+
+fn temp_path(test_name: &str, ext: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_attach_{test_name}_{}.{ext}", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(vault: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .args(["--path", vault, "--password", "testpw", "--no-session"])
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn blobs(vault: &str) -> usize {
+    fs::read_dir(format!("{vault}.attachments")).map_or(0, |dir| dir.count())
+}
+
+#[test]
+fn binary_files_round_trip_through_encrypted_blobs() {
+    let vault = temp_path("roundtrip", "json");
+    let v = vault.to_str().unwrap();
+    let contents: Vec<u8> = b"PRIVATE\0\xff\xfe".iter().copied().cycle().take(4096).collect();
+    let file = temp_path("roundtrip", "der");
+    fs::write(&file, &contents).unwrap();
+    let f = file.to_str().unwrap();
+
+    let attached = ownkey(v, &["attach", "tls_key", f, "--tag", "prod"]);
+    assert!(attached.status.success(), "{}", String::from_utf8_lossy(&attached.stderr));
+    assert_eq!(blobs(v), 1);
+    let vault_file = fs::read(&vault).unwrap();
+    assert!(!vault_file.windows(7).any(|w| w == b"PRIVATE"));
+
+    let out = temp_path("roundtrip", "out");
+    let o = out.to_str().unwrap();
+    assert!(ownkey(v, &["extract", "tls_key", "-o", o]).status.success());
+    assert_eq!(fs::read(&out).unwrap(), contents);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&out).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    let view = ownkey(v, &["view", "tls_key"]);
+    assert_eq!(view.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&view.stderr).contains("ownkey extract tls_key"));
+    assert_eq!(ownkey(v, &["attach", "tls_key", f]).status.code(), Some(2));
+    assert!(ownkey(v, &["add", "password", "hunter2"]).status.success());
+    assert_eq!(ownkey(v, &["attach", "password", f, "--force"]).status.code(), Some(2));
+    assert_eq!(ownkey(v, &["extract", "password"]).status.code(), Some(2));
+
+    fs::write(&file, b"replaced").unwrap();
+    assert!(ownkey(v, &["attach", "tls_key", f, "--force"]).status.success());
+    assert_eq!(blobs(v), 1, "the replaced blob is removed");
+    assert_eq!(ownkey(v, &["extract", "tls_key"]).stdout, b"replaced");
+
+    assert!(ownkey(v, &["delete", "tls_key", "--yes"]).status.success());
+    assert_eq!(blobs(v), 0);
+}
+
+#[test]
+fn mv_force_over_a_file_entry_removes_its_blob() {
+    let vault = temp_path("mv", "json");
+    let v = vault.to_str().unwrap();
+    let file = temp_path("mv", "pem");
+    fs::write(&file, b"old cert").unwrap();
+    let f = file.to_str().unwrap();
+
+    assert!(ownkey(v, &["attach", "old_cert", f]).status.success());
+    fs::write(&file, b"new cert").unwrap();
+    assert!(ownkey(v, &["attach", "new_cert", f]).status.success());
+    assert_eq!(blobs(v), 2);
+
+    assert!(ownkey(v, &["mv", "new_cert", "old_cert", "--force"]).status.success());
+    assert_eq!(blobs(v), 1, "the overwritten entry's blob is removed");
+    assert_eq!(ownkey(v, &["extract", "old_cert"]).stdout, b"new cert");
+}
+
+#[test]
+fn oversized_files_are_rejected() {
+    let vault = temp_path("oversized", "json");
+    let v = vault.to_str().unwrap();
+    let file = temp_path("oversized", "bin");
+    fs::File::create(&file)
+        .unwrap()
+        .set_len(ownkey::attachment::MAX_SIZE + 1)
+        .unwrap();
+
+    let output = ownkey(v, &["attach", "big", file.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("limited to 10 MiB"));
+    assert_eq!(blobs(v), 0);
+}
@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_audit_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(vault: &str, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .args(["--path", vault, "--password", "testpw", "--no-session"])
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn init(test_name: &str) -> PathBuf {
+    let vault = temp_vault_path(test_name);
+    let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["init", vault.to_str().unwrap(), "--password", "testpw", "--no-session"])
+        .output()
+        .expect("failed to run ownkey");
+    assert!(output.status.success());
+    vault
+}
+
+#[test]
+fn view_warns_about_expired_entries() {
+    let vault = init("view");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(v, &["add", "old_key", "abc", "--expires", "2020-01-01"]).status.success());
+
+    let output = ownkey(v, &["view", "old_key"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "abc");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'old_key' expired on 2020-01-01"));
+
+    assert!(ownkey(v, &["policy", "old_key", "--no-expiry"]).status.success());
+    let output = ownkey(v, &["view", "old_key"]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("expired"));
+
+    let huge = ownkey(v, &["policy", "old_key", "--rotate-every", "100000000d"]);
+    assert_eq!(huge.status.code(), Some(2), "durations past chrono's date range are rejected");
+    assert_eq!(ownkey(v, &["audit", "--within", "100000000d"]).status.code(), Some(2));
+}
+
+#[test]
+fn audit_json_lists_findings_without_values() {
+    let vault = init("json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(v, &["add", "expired", "s3cret-one", "--expires", "2020-01-01"]).status.success());
+    assert!(ownkey(v, &["add", "soon", "s3cret-two"]).status.success());
+    assert!(ownkey(v, &["add", "copy_of_soon", "s3cret-two"]).status.success());
+    assert!(ownkey(v, &["add", "fresh", "s3cret-three"]).status.success());
+    assert!(ownkey(v, &["update", "fresh", "s3cret-four", "--yes"]).status.success());
+
+    let tomorrow = (chrono::Utc::now() + chrono::Duration::days(1)).to_rfc3339();
+    assert!(ownkey(v, &["policy", "soon", "--expires", &tomorrow]).status.success());
+
+    let output = ownkey(v, &["audit", "--json"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("s3cret"), "audit must not print values");
+
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(report["expired"][0]["key"], "expired");
+    assert_eq!(report["expiring_soon"][0]["key"], "soon");
+    assert_eq!(report["duplicates"], serde_json::json!([["copy_of_soon", "soon"]]));
+    assert_eq!(
+        report["never_rotated"],
+        serde_json::json!(["copy_of_soon", "expired", "soon"])
+    );
+
+    let output = ownkey(v, &["audit", "--within", "1h"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Expiring soon"));
+    assert!(stdout.contains("Duplicate values (1):\n  copy_of_soon, soon"));
+}
+
+#[test]
+fn audit_strength_reports_weak_values_by_key() {
+    let vault = init("strength");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(v, &["add", "db_password", "changeme"]).status.success());
+    assert!(ownkey(v, &["add", "github_token", "ghp_truncated"]).status.success());
+    assert!(ownkey(v, &["add", "api_key", "--generate"]).status.success());
+
+    let output = ownkey(v, &["audit"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Weak values"));
+
+    let output = ownkey(v, &["audit", "--strength"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Weak values (2):"));
+    assert!(stdout.contains("  db_password  test or placeholder value"));
+    assert!(stdout.contains("  github_token  looks like a malformed GitHub token"));
+    assert!(!stdout.contains("changeme") && !stdout.contains("ghp_"));
+
+    let output = ownkey(v, &["audit", "--strength", "--json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report["weak"],
+        serde_json::json!([
+            { "key": "db_password", "issue": "placeholder" },
+            { "key": "github_token", "issue": "malformed", "format": "GitHub token" },
+        ])
+    );
+}
+
+#[test]
+fn audit_breach_db_checks_values_offline() {
+    let vault = init("breach");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(v, &["add", "wifi", "password"]).status.success());
+    assert!(ownkey(v, &["add", "api_key", "--generate"]).status.success());
+
+    // SHA-1 of "password", among other hashes, ordered by hash.
+    let db = temp_vault_path("pwned").with_extension("txt");
+    std::fs::write(
+        &db,
+        "000000005AD76BD555C1D6D771DE417A4B87E4B4:4\n\
+         5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\n\
+         FFFFFFFF0000000000000000000000000000000A:1\n",
+    )
+    .unwrap();
+
+    let output = ownkey(v, &["audit", "--breach-db", db.to_str().unwrap(), "--json"]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["breached"], serde_json::json!([{ "key": "wifi", "count": 9545824 }]));
+
+    let missing = ownkey(v, &["audit", "--breach-db", "/nonexistent/pwned.txt"]);
+    assert_eq!(missing.status.code(), Some(1));
+}
@@ -136,3 +136,110 @@ fn corrupted_vault_file_causes_failure() {
         "error message should indicate corrupted vault file"
     );
 }
+
+#[test]
+fn failures_use_distinct_exit_codes() {
+    let path = temp_vault_path("exit_codes");
+    let path_str = path.to_str().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["init", path_str, "--password", "testpw"])
+        .status()
+        .expect("failed to run ownkey init");
+    assert!(status.success(), "init should succeed before view");
+
+    let missing = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["view", "--path", path_str, "--no-session", "--password", "testpw", "missing_key"])
+        .output()
+        .expect("failed to run ownkey view");
+    assert_eq!(missing.status.code(), Some(3), "missing key should exit with 3");
+
+    let wrong_pw = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["view", "--path", path_str, "--no-session", "--password", "wrongpw", "missing_key"])
+        .output()
+        .expect("failed to run ownkey view with wrong password");
+    assert_eq!(wrong_pw.status.code(), Some(4), "wrong password should exit with 4");
+}
+
+#[test]
+fn error_format_json_reports_kind_and_exit_code() {
+    let path = temp_vault_path("error_json");
+    let path_str = path.to_str().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["init", path_str, "--password", "testpw"])
+        .status()
+        .expect("failed to run ownkey init");
+    assert!(status.success(), "init should succeed before view");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["--error-format", "json", "view", "--path", path_str, "--password", "testpw"])
+        .arg("missing_key")
+        .output()
+        .expect("failed to run ownkey view");
+    assert_eq!(output.status.code(), Some(3));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr
+        .lines()
+        .find(|l| l.starts_with('{'))
+        .expect("stderr should contain a JSON error object");
+    let value: serde_json::Value = serde_json::from_str(line).expect("error should be valid JSON");
+    assert_eq!(value["error"]["kind"], "not_found");
+    assert_eq!(value["error"]["exit_code"], 3);
+}
+
+#[test]
+fn view_without_key_needs_a_terminal_for_the_picker() {
+    let path = temp_vault_path("pick_no_tty");
+    let path_str = path.to_str().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["init", path_str, "--password", "testpw"])
+        .status()
+        .expect("failed to run ownkey init");
+    assert!(status.success(), "init should succeed before view");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["view", "--path", path_str, "--password", "testpw"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("failed to run ownkey view");
+    assert_eq!(output.status.code(), Some(2), "picker without a terminal is a usage error");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("terminal"), "stderr should explain why: {stderr}");
+}
+
+#[test]
+fn copy_validates_clear_after_and_reports_clipboard_failures() {
+    let path = temp_vault_path("copy_clear");
+    let path_str = path.to_str().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["init", path_str, "--password", "testpw"])
+        .status()
+        .expect("failed to run ownkey init");
+    assert!(status.success(), "init should succeed before copy");
+    let status = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["add", "--path", path_str, "--password", "testpw", "k", "v"])
+        .status()
+        .expect("failed to run ownkey add");
+    assert!(status.success(), "add should succeed before copy");
+
+    let bad = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["copy", "--path", path_str, "--password", "testpw", "k", "--clear-after", "soon"])
+        .output()
+        .expect("failed to run ownkey copy");
+    assert_eq!(bad.status.code(), Some(2), "invalid --clear-after should be a usage error");
+
+    // Without a display the background helper cannot take the clipboard; its
+    // error must still surface from `ownkey copy` itself.
+    if cfg!(target_os = "linux")
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+    {
+        let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+            .args(["copy", "--path", path_str, "--password", "testpw", "k", "--clear-after", "5s"])
+            .output()
+            .expect("failed to run ownkey copy");
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("clipboard"), "stderr should mention the clipboard: {stderr}");
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use uuid::Uuid;
+
+fn temp_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ownkey_config_{test_name}_{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Run ownkey with its own home directory and the given environment.
+fn ownkey(home: &Path, env: &[(&str, &str)], args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .env("HOME", home)
+        .env_remove("OWNKEY_VAULT")
+        .envs(env.iter().copied())
+        .current_dir(home)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+#[test]
+fn global_options_come_from_flags_environment_and_config() {
+    let home = temp_dir("home");
+    fs::create_dir_all(home.join(".ownkey")).unwrap();
+    fs::write(home.join(".ownkey/config.toml"), "keychain_account = \"me\"\nno_session = false\n").unwrap();
+    let password_file = home.join("pw");
+    fs::write(&password_file, "testpw\n").unwrap();
+    let vault = home.join("vault.json");
+    let v = vault.to_str().unwrap();
+    let env = [("OWNKEY_PASSWORD_FILE", password_file.to_str().unwrap()), ("OWNKEY_NO_SESSION", "1")];
+
+    // Global options go before or after the subcommand.
+    let init = ownkey(&home, &env, &["--path", v, "init"]);
+    assert!(init.status.success(), "{}", String::from_utf8_lossy(&init.stderr));
+    assert!(ownkey(&home, &env, &["-p", v, "add", "api_key", "s3cret"]).status.success());
+    let view = ownkey(&home, &env, &["view", "api_key", "--path", v]);
+    assert_eq!(String::from_utf8_lossy(&view.stdout).trim(), "s3cret");
+    let stderr = String::from_utf8_lossy(&view.stderr);
+    assert!(!stderr.contains("--password on the command line"), "{stderr}");
+
+    let wrong = home.join("wrong");
+    fs::write(&wrong, "nope\n").unwrap();
+    let env_wrong = [("OWNKEY_PASSWORD_FILE", wrong.to_str().unwrap()), ("OWNKEY_NO_SESSION", "1")];
+    assert_eq!(ownkey(&home, &env_wrong, &["view", "api_key", "-p", v]).status.code(), Some(4));
+    let flag = ownkey(&home, &env_wrong, &["view", "api_key", "-p", v, "--password-file", password_file.to_str().unwrap()]);
+    assert!(flag.status.success(), "the flag wins over the environment");
+
+    let show = ownkey(&home, &env, &["config", "show", "--json", "-p", v]);
+    let json: serde_json::Value = serde_json::from_slice(&show.stdout).unwrap();
+    assert_eq!(json["path"]["value"], v);
+    assert_eq!(json["path"]["source"], "--path");
+    assert_eq!(json["password"]["source"], "$OWNKEY_PASSWORD_FILE");
+    assert_eq!(json["no_session"]["value"], true);
+    assert_eq!(json["no_session"]["source"], "$OWNKEY_NO_SESSION");
+    assert_eq!(json["keychain_account"]["value"], "me");
+    assert_eq!(json["keychain_account"]["source"], "config.toml keychain_account");
+    assert_eq!(json["keychain_service"]["source"], "default");
+    assert!(json["vault"]["value"].is_null());
+
+    let text = String::from_utf8_lossy(&ownkey(&home, &[], &["config", "show", "--password", "x"]).stdout).to_string();
+    assert!(text.contains("(hidden)") && !text.contains(" x "), "{text}");
+
+    let bad = ownkey(&home, &[("OWNKEY_NO_SESSION", "maybe")], &["list", "-p", v]);
+    assert_eq!(bad.status.code(), Some(2));
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_env_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+#[test]
+#[cfg(unix)]
+fn bash_output_round_trips_through_eval() {
+    let path = temp_vault_path("eval");
+    let p = path.to_str().unwrap();
+    assert!(ownkey(&["init", p, "--password", "testpw"]).status.success());
+    let tricky = "a'b \"c\" $HOME\nline2";
+    assert!(ownkey(&["add", "--path", p, "--password", "testpw", "prod/db-url", tricky])
+        .status
+        .success());
+    assert!(ownkey(&["add", "--path", p, "--password", "testpw", "prod/token", "t0k"])
+        .status
+        .success());
+    assert!(ownkey(&["add", "--path", p, "--password", "testpw", "staging/token", "other"])
+        .status
+        .success());
+
+    let output = ownkey(&["env", "--path", p, "--password", "testpw", "--prefix", "prod/"]);
+    assert!(output.status.success(), "env should succeed");
+    let script = String::from_utf8_lossy(&output.stdout).to_string();
+    assert!(!script.contains("STAGING"), "prefix should only select matching keys");
+
+    let shell = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{script}\nprintf '%s|%s' \"$PROD_DB_URL\" \"$PROD_TOKEN\""))
+        .output()
+        .expect("failed to run sh");
+    assert_eq!(String::from_utf8_lossy(&shell.stdout), format!("{tricky}|t0k"));
+}
+
+#[test]
+fn dotenv_format_and_missing_keys() {
+    let path = temp_vault_path("dotenv");
+    let p = path.to_str().unwrap();
+    assert!(ownkey(&["init", p, "--password", "testpw"]).status.success());
+    assert!(ownkey(&["add", "--path", p, "--password", "testpw", "api-key", "xyz"])
+        .status
+        .success());
+
+    let output = ownkey(&["env", "--path", p, "--password", "testpw", "--format", "dotenv", "api-key"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "API_KEY=\"xyz\"");
+
+    let missing = ownkey(&["env", "--path", p, "--password", "testpw", "nope"]);
+    assert_eq!(missing.status.code(), Some(3), "missing key should exit with 3");
+}
+
+#[test]
+fn variable_names_can_drop_the_key_prefix_and_gain_their_own() {
+    let path = temp_vault_path("names");
+    let p = path.to_str().unwrap();
+    assert!(ownkey(&["add", "--path", p, "--password", "testpw", "prod/db-url", "pg://"]).status.success());
+
+    let env = |extra: &[&str]| {
+        let base = ["env", "--path", p, "--password", "testpw", "--format", "dotenv", "--prefix", "prod/"];
+        let output = ownkey(&[&base[..], extra].concat());
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    assert_eq!(env(&[]), "PROD_DB_URL=\"pg://\"");
+    assert_eq!(env(&["--strip-prefix"]), "DB_URL=\"pg://\"");
+    assert_eq!(env(&["--strip-prefix", "--var-prefix", "PROJECT_"]), "PROJECT_DB_URL=\"pg://\"");
+    assert_eq!(env(&["--var-prefix", "app_"]), "APP_PROD_DB_URL=\"pg://\"");
+}
+
+#[test]
+fn attached_files_are_skipped_unless_named() {
+    let path = temp_vault_path("files");
+    let p = path.to_str().unwrap();
+    let file = std::env::temp_dir().join(format!("ownkey_env_file_{}.pem", Uuid::new_v4()));
+    std::fs::write(&file, "-----BEGIN KEY-----\n").unwrap();
+    assert!(ownkey(&["add", "--path", p, "--password", "testpw", "prod/token", "t0k"]).status.success());
+    assert!(ownkey(&["attach", "--path", p, "--password", "testpw", "prod/tls", file.to_str().unwrap()])
+        .status
+        .success());
+
+    let base = ["env", "--path", p, "--password", "testpw", "--format", "dotenv"];
+    let output = ownkey(&[&base[..], &["--prefix", "prod/"]].concat());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "PROD_TOKEN=\"t0k\"");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Skipping 1 file attachment(s)"));
+
+    let named = ownkey(&[&base[..], &["prod/tls"]].concat());
+    assert_eq!(named.status.code(), Some(2), "a file named explicitly is an error");
+}
@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use uuid::Uuid;
+
+fn temp_path(test_name: &str, ext: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_export_{test_name}_{}.{ext}", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn seeded_vault(test_name: &str) -> PathBuf {
+    let vault = temp_path(test_name, "json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+    for (k, val) in [("prod/db", "pg"), ("prod/api", "sk"), ("dev/db", "local")] {
+        assert!(ownkey(&["add", "--path", v, "--password", "testpw", k, val])
+            .status
+            .success());
+    }
+    vault
+}
+
+#[test]
+fn plaintext_export_requires_confirmation() {
+    let vault = seeded_vault("confirm");
+    let v = vault.to_str().unwrap();
+
+    let refused = ownkey(&["export", "--path", v, "--password", "testpw", "--format", "json"]);
+    assert_eq!(refused.status.code(), Some(2), "plaintext export without --plaintext should fail");
+    assert!(refused.stdout.is_empty());
+
+    let output = ownkey(&[
+        "export", "--path", v, "--password", "testpw", "--format", "json", "--plaintext",
+        "--prefix", "prod/",
+    ]);
+    assert!(output.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "prod/api": {"value": "sk", "kind": "secret", "fields": {}, "tags": []},
+            "prod/db": {"value": "pg", "kind": "secret", "fields": {}, "tags": []},
+        })
+    );
+}
+
+#[test]
+fn encrypted_export_is_a_vault_under_the_export_passphrase() {
+    let vault = seeded_vault("encrypted");
+    let v = vault.to_str().unwrap();
+    let out = temp_path("encrypted_out", "json");
+    let o = out.to_str().unwrap();
+
+    let output = ownkey(&[
+        "export", "--path", v, "--password", "testpw", "--format", "ownkey-encrypted",
+        "--export-password", "exportpw", "--prefix", "prod/", "-o", o,
+    ]);
+    assert!(output.status.success(), "encrypted export should succeed");
+    let file: serde_json::Value = serde_json::from_slice(&fs::read(&out).unwrap()).unwrap();
+    let ciphertext = general_purpose::STANDARD
+        .decode(file["ciphertext"].as_str().expect("ciphertext field"))
+        .expect("ciphertext should be base64");
+    assert!(!ciphertext.windows(b"prod/db".len()).any(|w| w == b"prod/db"));
+
+    let decrypted = ownkey(&[
+        "export", "--path", o, "--no-session", "--password", "exportpw", "--format", "json",
+        "--plaintext",
+    ]);
+    assert!(decrypted.status.success(), "export should open as a vault with the export passphrase");
+    let entries: serde_json::Value = serde_json::from_slice(&decrypted.stdout).unwrap();
+    let values: Vec<(&str, &str)> = entries
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(k, v)| (k.as_str(), v["value"].as_str().unwrap()))
+        .collect();
+    assert_eq!(values, vec![("prod/api", "sk"), ("prod/db", "pg")]);
+
+    let wrong = ownkey(&["list", "--path", o, "--no-session", "--password", "testpw"]);
+    assert_eq!(wrong.status.code(), Some(4), "vault password must not open the export");
+}
+
+#[test]
+fn json_export_round_trips_typed_entries_through_import() {
+    let vault = seeded_vault("typed");
+    let v = vault.to_str().unwrap();
+    let login = [
+        "add", "--path", v, "--password", "testpw", "site", "pw1", "--kind", "login",
+        "--field", "username=me", "--field", "url=https://example.com", "--tag", "web",
+    ];
+    assert!(ownkey(&login).status.success());
+
+    let dotenv = ownkey(&["export", "--path", v, "--password", "testpw", "--format", "dotenv", "--plaintext"]);
+    assert!(dotenv.status.success());
+    assert!(String::from_utf8_lossy(&dotenv.stderr).contains("dropping the fields of 1 typed entry"));
+
+    let out = temp_path("typed_out", "json");
+    let o = out.to_str().unwrap();
+    let exported = ownkey(&[
+        "export", "--path", v, "--password", "testpw", "--format", "json", "--plaintext", "-o", o,
+    ]);
+    assert!(exported.status.success());
+    assert!(String::from_utf8_lossy(&exported.stderr).lines().all(|l| !l.contains("dropping")));
+
+    let copy = temp_path("typed_copy", "json");
+    let c = copy.to_str().unwrap();
+    assert!(ownkey(&["init", c, "--password", "testpw"]).status.success());
+    let imported = ownkey(&["import", "--path", c, "--password", "testpw", "--format", "json", o]);
+    assert!(imported.status.success(), "{}", String::from_utf8_lossy(&imported.stderr));
+
+    let view = ownkey(&["view", "--path", c, "--password", "testpw", "site", "--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&view.stdout).unwrap();
+    assert_eq!(json["kind"], "login");
+    assert_eq!(json["fields"]["username"], "me");
+    assert_eq!(json["fields"]["url"], "https://example.com");
+    assert_eq!(json["tags"], serde_json::json!(["web"]));
+    let db = ownkey(&["view", "--path", c, "--password", "testpw", "dev/db"]);
+    assert_eq!(String::from_utf8_lossy(&db.stdout).trim(), "local");
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_generate_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+#[test]
+fn generate_prints_secret_and_entropy() {
+    let output = ownkey(&["generate", "--format", "hex", "--length", "8"]);
+    assert!(output.status.success());
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(secret.len(), 16);
+    assert!(secret.chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("~64 bits"));
+
+    let phrase = ownkey(&["generate", "--format", "passphrase", "--words", "4", "--separator", " "]);
+    assert_eq!(String::from_utf8_lossy(&phrase.stdout).split_whitespace().count(), 4);
+
+    let impossible = ownkey(&["generate", "--length", "2"]);
+    assert_eq!(impossible.status.code(), Some(2), "too short for all classes");
+}
+
+#[test]
+fn add_generate_stores_a_fresh_value() {
+    let vault = temp_vault_path("add");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+
+    let added = ownkey(&[
+        "add", "--path", v, "--password", "testpw", "db_password", "--generate", "--length", "32",
+        "--no-symbols",
+    ]);
+    assert!(added.status.success(), "add --generate should succeed");
+
+    let view = ownkey(&["view", "--path", v, "--password", "testpw", "db_password"]);
+    let value = String::from_utf8_lossy(&view.stdout).trim().to_string();
+    assert_eq!(value.len(), 32);
+    assert!(value.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    let both = ownkey(&["add", "--path", v, "--password", "testpw", "k", "value", "--generate"]);
+    assert_eq!(both.status.code(), Some(2), "an explicit value conflicts with --generate");
+
+    for args in [&["k", "value", "--length", "5"][..], &["k", "--words", "3"], &["k", "--stdin", "--no-symbols"]] {
+        let stray = ownkey(&[&["add", "--path", v, "--password", "testpw"][..], args].concat());
+        assert_eq!(stray.status.code(), Some(2), "generator options need --generate: {args:?}");
+    }
+    let stray = ownkey(&["update", "--path", v, "--password", "testpw", "db_password", "v", "--format", "hex"]);
+    assert_eq!(stray.status.code(), Some(2));
+
+    let _ = fs::remove_file(&vault);
+}
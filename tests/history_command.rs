@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_history_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(vault: &str, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .args(["--path", vault, "--password", "testpw", "--no-session"])
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn overwritten_values_can_be_viewed_and_rolled_back() {
+    let vault = temp_vault_path("rollback");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(v, &["add", "api_key", "first"]).status.success());
+    for value in ["second", "third"] {
+        assert!(ownkey(v, &["update", "api_key", value, "--yes"]).status.success());
+    }
+
+    let history = stdout(&ownkey(v, &["history", "api_key"]));
+    let lines: Vec<&str> = history.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("3  ") && lines[0].ends_with("(current)"));
+    assert!(lines[2].starts_with("1  "));
+    assert!(!history.contains("first"), "history must not print values");
+
+    assert_eq!(stdout(&ownkey(v, &["view", "api_key", "--version", "1"])), "first");
+    let missing = ownkey(v, &["view", "api_key", "--version", "9"]);
+    assert_eq!(missing.status.code(), Some(2));
+
+    let rolled = ownkey(v, &["rollback", "api_key", "--to", "1"]);
+    assert!(rolled.status.success());
+    assert!(stdout(&rolled).contains("now version 4"));
+    assert_eq!(stdout(&ownkey(v, &["view", "api_key"])), "first");
+    assert_eq!(stdout(&ownkey(v, &["view", "api_key", "--version", "3"])), "third");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout(&ownkey(v, &["history", "api_key", "--json"]))).unwrap();
+    assert_eq!(json[0]["version"], 4);
+    assert_eq!(json[0]["current"], true);
+    assert_eq!(json.as_array().unwrap().len(), 4);
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_path(test_name: &str, ext: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_import_{test_name}_{}.{ext}", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn view(vault: &str, key: &str) -> String {
+    let output = ownkey(&["view", "--path", vault, "--password", "testpw", key]);
+    assert!(output.status.success(), "view {key} should succeed");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn import_dotenv_with_conflict_policies_and_dry_run() {
+    let vault = temp_path("dotenv", "json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+    assert!(ownkey(&["add", "--path", v, "--password", "testpw", "API_KEY", "old"])
+        .status
+        .success());
+
+    let file = temp_path("dotenv", "env");
+    fs::write(&file, "API_KEY=new\nDB_URL=\"postgres://x\"\n").unwrap();
+    let f = file.to_str().unwrap();
+    let base = ["import", "--path", v, "--password", "testpw", "--format", "dotenv", f];
+
+    let dry = ownkey(&[&base[..], &["--on-conflict", "overwrite", "--dry-run"]].concat());
+    assert!(dry.status.success(), "dry run should succeed");
+    let stdout = String::from_utf8_lossy(&dry.stdout);
+    assert!(stdout.contains("1 imported, 1 overwritten, 0 renamed, 0 skipped"));
+    assert!(!stdout.contains("postgres://x"), "values must never be printed");
+    assert_eq!(view(v, "API_KEY"), "old", "dry run must not modify the vault");
+
+    let skip = ownkey(&base);
+    assert!(skip.status.success());
+    assert!(String::from_utf8_lossy(&skip.stdout).contains("1 imported, 0 overwritten, 0 renamed, 1 skipped"));
+    assert_eq!(view(v, "API_KEY"), "old");
+    assert_eq!(view(v, "DB_URL"), "postgres://x");
+
+    let rename = ownkey(&[&base[..], &["--on-conflict", "rename"]].concat());
+    assert!(rename.status.success());
+    assert_eq!(view(v, "API_KEY_2"), "new");
+}
+
+#[test]
+fn import_rejects_malformed_input() {
+    let vault = temp_path("malformed", "json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+
+    let file = temp_path("malformed", "json");
+    fs::write(&file, "[1, 2, 3]").unwrap();
+    let output = ownkey(&[
+        "import", "--path", v, "--password", "testpw", "--format", "json",
+        file.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(2), "invalid input should exit with 2");
+}
+
+#[test]
+fn import_reports_duplicate_keys_instead_of_renaming_them() {
+    let vault = temp_path("duplicates", "json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+
+    let file = temp_path("duplicates", "env");
+    fs::write(&file, "TOKEN=first\nTOKEN=second\n").unwrap();
+    let output = ownkey(&[
+        "import", "--path", v, "--password", "testpw", "--format", "dotenv",
+        "--on-conflict", "rename", file.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("skipped TOKEN (duplicate key in"), "{stdout}");
+    assert!(stdout.contains("1 imported, 0 overwritten, 0 renamed, 1 skipped"), "{stdout}");
+    assert_eq!(view(v, "TOKEN"), "first");
+    let missing = ownkey(&["view", "--path", v, "--password", "testpw", "TOKEN_2"]);
+    assert_eq!(missing.status.code(), Some(3));
+}
+
+#[test]
+fn import_overwrite_keeps_file_and_totp_entries_valid() {
+    let vault = temp_path("typed", "json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+    let cert = temp_path("typed_cert", "pem");
+    fs::write(&cert, "-----BEGIN CERTIFICATE-----\n").unwrap();
+    assert!(ownkey(&["attach", "--path", v, "--password", "testpw", "TLS", cert.to_str().unwrap()])
+        .status
+        .success());
+    assert!(ownkey(&["add", "--path", v, "--password", "testpw", "OTP", "JBSWY3DPEHPK3PXP", "--kind", "totp"])
+        .status
+        .success());
+
+    let file = temp_path("typed", "env");
+    fs::write(&file, "TLS=oops\nOTP=not-base32!\n").unwrap();
+    let output = ownkey(&[
+        "import", "--path", v, "--password", "testpw", "--format", "dotenv",
+        "--on-conflict", "overwrite", file.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("skipped TLS (file attachment"), "{stdout}");
+    assert!(stdout.contains("skipped OTP (invalid base32"), "{stdout}");
+    assert!(stdout.contains("0 imported, 0 overwritten, 0 renamed, 2 skipped"), "{stdout}");
+
+    let extracted = ownkey(&["extract", "--path", v, "--password", "testpw", "TLS"]);
+    assert_eq!(String::from_utf8_lossy(&extracted.stdout), "-----BEGIN CERTIFICATE-----\n");
+    assert_eq!(view(v, "OTP"), "JBSWY3DPEHPK3PXP");
+
+    // A valid seed may replace a TOTP entry, which stays a TOTP entry.
+    fs::write(&file, "OTP=GEZDGNBVGY3TQOJQ\n").unwrap();
+    let output = ownkey(&[
+        "import", "--path", v, "--password", "testpw", "--format", "dotenv",
+        "--on-conflict", "overwrite", file.to_str().unwrap(),
+    ]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("overwrote OTP"));
+    let totp = ownkey(&["totp", "--path", v, "--password", "testpw", "OTP"]);
+    assert!(totp.status.success());
+}
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use ownkey::{OwnkeyError, Unlock, Vault};
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_lib_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+#[test]
+fn create_set_save_and_reopen() {
+    let path = temp_vault_path("round_trip");
+
+    let mut vault = Vault::create(&path, "testpw").expect("create should succeed");
+    assert!(vault.list().is_empty(), "new vault should be empty");
+    assert_eq!(vault.set("b_key", "2"), None);
+    assert_eq!(vault.set("a_key", "1"), None);
+    vault.save().expect("save should succeed");
+
+    let vault = Vault::open(&path, Unlock::Password("testpw".into())).expect("open should succeed");
+    assert_eq!(vault.list(), vec!["a_key", "b_key"], "list should be sorted");
+    assert_eq!(vault.get("a_key").unwrap(), "1");
+}
+
+#[test]
+fn typed_errors_are_returned() {
+    let path = temp_vault_path("errors");
+
+    match Vault::open(&path, Unlock::Password("testpw".into())) {
+        Err(OwnkeyError::VaultNotFound(_)) => {}
+        other => panic!("expected VaultNotFound, got {:?}", other.err()),
+    }
+
+    let mut vault = Vault::create(&path, "testpw").expect("create should succeed");
+    assert!(matches!(vault.get("missing"), Err(OwnkeyError::NotFound(_))));
+    assert!(matches!(vault.remove("missing"), Err(OwnkeyError::NotFound(_))));
+
+    match Vault::open(&path, Unlock::Password("wrongpw".into())) {
+        Err(OwnkeyError::WrongPassword) => {}
+        other => panic!("expected WrongPassword, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn library_and_cli_share_the_vault_format() {
+    let path = temp_vault_path("cli_interop");
+    let path_str = path.to_str().unwrap();
+
+    let mut vault = Vault::create(&path, "testpw").expect("create should succeed");
+    vault.set("lib_key", "lib_value");
+    vault.save().expect("save should succeed");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["view", "--path", path_str, "--no-session", "--password", "testpw", "lib_key"])
+        .output()
+        .expect("failed to run ownkey view");
+    assert!(output.status.success(), "CLI should read a vault written by the library");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("lib_value"));
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_log_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(vault: &str, password: &str, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .args(["--path", vault, "--password", password, "--no-session"])
+        .output()
+        .expect("failed to run ownkey")
+}
+
+#[test]
+fn access_log_records_reads_and_changes_across_password_rotation() {
+    let vault = temp_vault_path("records");
+    let v = vault.to_str().unwrap();
+    let init = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["init", v, "--password", "testpw", "--no-session"])
+        .output()
+        .unwrap();
+    assert!(init.status.success());
+
+    assert!(ownkey(v, "testpw", &["add", "db", "s3cret"]).status.success());
+    assert!(ownkey(v, "testpw", &["view", "db"]).status.success());
+    assert_eq!(ownkey(v, "testpw", &["view", "missing"]).status.code(), Some(3));
+    assert!(ownkey(v, "testpw", &["rotate-password", "--new-password", "newpw"]).status.success());
+    assert!(ownkey(v, "newpw", &["delete", "db", "--yes"]).status.success());
+
+    let log = fs::read_to_string(format!("{v}.log")).unwrap();
+    assert!(!log.contains("s3cret") && !log.contains("\"db\""), "log must be encrypted");
+
+    let output = ownkey(v, "newpw", &["log", "--json"]);
+    assert!(output.status.success());
+    let records: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let summary: Vec<(String, String, String)> = records
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["command"].as_str().unwrap().to_string(),
+                r["key"].as_str().unwrap_or("-").to_string(),
+                r["result"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    let expected = [
+        ("add", "db", "ok"),
+        ("view", "db", "ok"),
+        ("view", "missing", "error: No entry found for key missing"),
+        ("rotate-password", "-", "ok"),
+        ("delete", "db", "ok"),
+    ];
+    assert_eq!(summary.len(), expected.len());
+    for ((command, key, result), (c, k, r)) in summary.iter().zip(expected) {
+        assert_eq!((command.as_str(), key.as_str(), result.as_str()), (c, k, r));
+    }
+
+    let filtered = ownkey(v, "newpw", &["log", "--command", "view", "--limit", "1"]);
+    let stdout = String::from_utf8_lossy(&filtered.stdout);
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.contains("view  missing"));
+
+    let verified = ownkey(v, "newpw", &["log", "verify"]);
+    assert!(verified.status.success());
+    assert!(String::from_utf8_lossy(&verified.stdout).contains("5 records"));
+}
+
+#[test]
+fn log_verify_detects_truncation() {
+    let vault = temp_vault_path("truncate");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(v, "testpw", &["add", "a", "1"]).status.success());
+    assert!(ownkey(v, "testpw", &["add", "b", "2"]).status.success());
+
+    let log_path = format!("{v}.log");
+    let log = fs::read_to_string(&log_path).unwrap();
+    let kept: Vec<&str> = log.lines().take(2).collect();
+    fs::write(&log_path, kept.join("\n") + "\n").unwrap();
+
+    let output = ownkey(v, "testpw", &["log", "verify"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("truncated"));
+}
+
+#[test]
+fn concurrent_commands_keep_the_chain_intact() {
+    let vault = temp_vault_path("concurrent");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(v, "testpw", &["add", "db", "s3cret"]).status.success());
+
+    let views: Vec<_> = (0..20)
+        .map(|_| {
+            Command::new(env!("CARGO_BIN_EXE_ownkey"))
+                .args(["view", "db", "--path", v, "--password", "testpw", "--no-session"])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .expect("failed to run ownkey")
+        })
+        .collect();
+    for mut view in views {
+        assert!(view.wait().unwrap().success());
+    }
+
+    let output = ownkey(v, "testpw", &["log", "verify"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("21 records"));
+}
+
+#[test]
+fn sync_is_logged_to_the_selected_vault() {
+    let home = std::env::temp_dir().join(format!("ownkey_log_home_{}", Uuid::new_v4()));
+    fs::create_dir_all(&home).unwrap();
+    let vault = home.join("synced.json");
+    let v = vault.to_str().unwrap();
+    // Sync reads the unlocked key from the session, so the test keeps one in its own home.
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ownkey"))
+            .args(args)
+            .args(["--path", v])
+            .env("HOME", &home)
+            .output()
+            .expect("failed to run ownkey")
+    };
+    assert!(run(&["init", "--password", "testpw"]).status.success());
+    assert!(run(&["list", "--password", "testpw"]).status.success());
+
+    let synced = run(&["sync"]);
+    assert!(synced.status.success(), "{}", String::from_utf8_lossy(&synced.stderr));
+
+    let output = ownkey(v, "testpw", &["log", "--command", "sync"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("sync  -  ok"));
+    assert!(!home.join(".ownkey/vault.json.log").exists(), "the default vault is not touched");
+}
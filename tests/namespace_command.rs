@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_namespace_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn seeded_vault(test_name: &str) -> PathBuf {
+    let vault = temp_vault_path(test_name);
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+    for (k, val) in [
+        ("prod/db/password", "pg"),
+        ("prod/api", "sk"),
+        ("staging/api", "st"),
+        ("zeta", "z"),
+    ] {
+        assert!(ownkey(&["add", "--path", v, "--password", "testpw", k, val])
+            .status
+            .success());
+    }
+    vault
+}
+
+#[test]
+fn list_filters_by_namespace_and_renders_tree() {
+    let vault = seeded_vault("list");
+    let v = vault.to_str().unwrap();
+
+    let all = ownkey(&["list", "--path", v, "--password", "testpw"]);
+    assert_eq!(
+        String::from_utf8_lossy(&all.stdout),
+        "prod/api\nprod/db/password\nstaging/api\nzeta\n",
+        "list output should be sorted"
+    );
+
+    let prod = ownkey(&["list", "prod/", "--path", v, "--password", "testpw"]);
+    assert_eq!(String::from_utf8_lossy(&prod.stdout), "prod/api\nprod/db/password\n");
+
+    let tree = ownkey(&["list", "prod", "--tree", "--path", v, "--password", "testpw"]);
+    assert_eq!(
+        String::from_utf8_lossy(&tree.stdout),
+        "prod/\n├── api\n└── db/\n    └── password\n"
+    );
+
+    let missing = ownkey(&["list", "nope/", "--path", v, "--password", "testpw"]);
+    assert_eq!(missing.status.code(), Some(3));
+
+    let _ = fs::remove_file(&vault);
+}
+
+#[test]
+fn mv_renames_keys_and_subtrees() {
+    let vault = seeded_vault("mv");
+    let v = vault.to_str().unwrap();
+
+    let clash = ownkey(&["mv", "staging/api", "prod/api", "--path", v, "--password", "testpw"]);
+    assert_eq!(clash.status.code(), Some(2), "mv must not overwrite without --force");
+
+    let moved = ownkey(&["mv", "prod/", "archive/prod/", "--path", v, "--password", "testpw"]);
+    assert!(moved.status.success(), "subtree move should succeed");
+
+    let all = ownkey(&["list", "--path", v, "--password", "testpw"]);
+    assert_eq!(
+        String::from_utf8_lossy(&all.stdout),
+        "archive/prod/api\narchive/prod/db/password\nstaging/api\nzeta\n"
+    );
+
+    let renamed = ownkey(&["mv", "zeta", "alpha", "--path", v, "--password", "testpw"]);
+    assert!(renamed.status.success());
+    let view = ownkey(&["view", "alpha", "--path", v, "--password", "testpw"]);
+    assert!(String::from_utf8_lossy(&view.stdout).contains('z'));
+
+    let _ = fs::remove_file(&vault);
+}
@@ -2,9 +2,12 @@ use std::fs;
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 use std::process::Command;
+use std::thread;
+use std::time::Duration;
 
 use uuid::Uuid;
 use directories::BaseDirs;
+use ownkey::vault_store;
 
 fn temp_vault() -> PathBuf {
     let mut path = std::env::temp_dir();
@@ -47,3 +50,30 @@ fn permissions_are_600() {
     let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
     assert_eq!(mode, 0o600, "vault permissions should be 600");
 }
+
+#[test]
+fn overlapping_commands_wait_for_the_vault_lock() {
+    let path = temp_vault();
+    let p = path.to_str().unwrap().to_string();
+    let ownkey = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ownkey"))
+            .args(args)
+            .args(["--path", &p, "--password", "testpw", "--no-session"])
+            .output()
+            .expect("run ownkey")
+    };
+    assert!(ownkey(&["add", "api_key", "v1"]).status.success());
+
+    let held = path.clone();
+    let holder = thread::spawn(move || {
+        vault_store::with_lock(&held, || {
+            thread::sleep(Duration::from_millis(800));
+            Ok(())
+        })
+    });
+    thread::sleep(Duration::from_millis(200));
+    let output = ownkey(&["view", "api_key"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "v1");
+    holder.join().unwrap().unwrap();
+}
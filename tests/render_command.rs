@@ -0,0 +1,73 @@
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_path(test_name: &str, ext: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_render_{test_name}_{}.{ext}", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+#[test]
+fn render_fills_placeholders_into_output_file() {
+    let vault = temp_path("fill", "json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+    assert!(ownkey(&["add", "--path", v, "--password", "testpw", "db/password", "hunter2"])
+        .status
+        .success());
+
+    let template = temp_path("fill", "tmpl");
+    fs::write(&template, "db:\n  password: {{ ownkey \"db/password\" }}\n").unwrap();
+    let out = temp_path("fill", "yaml");
+
+    let output = ownkey(&[
+        "render",
+        "--path",
+        v,
+        "--password",
+        "testpw",
+        template.to_str().unwrap(),
+        "-o",
+        out.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "render should succeed");
+    assert!(output.stdout.is_empty(), "secrets must not be echoed when -o is used");
+    assert_eq!(fs::read_to_string(&out).unwrap(), "db:\n  password: hunter2\n");
+    #[cfg(unix)]
+    {
+        let mode = fs::metadata(&out).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600, "rendered file should be 600");
+    }
+}
+
+#[test]
+fn render_fails_on_missing_keys_and_check_lists_keys() {
+    let vault = temp_path("missing", "json");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+
+    let template = temp_path("missing", "tmpl");
+    fs::write(&template, "{{ ownkey \"b\" }} {{ ownkey \"a\" }} {{ ownkey \"b\" }}").unwrap();
+    let t = template.to_str().unwrap();
+
+    let output = ownkey(&["render", "--path", v, "--password", "testpw", t]);
+    assert_eq!(output.status.code(), Some(3), "missing keys should exit with 3");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("a, b"));
+
+    // --check never unlocks the vault, so no password is needed.
+    let output = ownkey(&["render", "--path", "/nonexistent/vault.json", "--check", t]);
+    assert!(output.status.success(), "check should not need the vault");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a\nb\n");
+}
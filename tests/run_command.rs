@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_run_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn init_with_entry(path: &str, key: &str, value: &str) {
+    let bin = env!("CARGO_BIN_EXE_ownkey");
+    let status = Command::new(bin)
+        .args(["init", path, "--password", "testpw"])
+        .status()
+        .expect("failed to run ownkey init");
+    assert!(status.success(), "init should succeed");
+    let status = Command::new(bin)
+        .args(["add", "--path", path, "--password", "testpw", key, value])
+        .status()
+        .expect("failed to run ownkey add");
+    assert!(status.success(), "add should succeed");
+}
+
+#[test]
+#[cfg(unix)]
+fn run_injects_secret_into_child_environment() {
+    let path = temp_vault_path("inject");
+    let path_str = path.to_str().unwrap();
+    init_with_entry(path_str, "db/prod", "postgres://secret");
+
+    let map_file = path.with_extension("env");
+    fs::write(&map_file, "# mapping\nMAPPED_URL=db/prod\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["run", "--path", path_str, "--password", "testpw"])
+        .args(["--env", "DB_URL=db/prod", "--env-file", map_file.to_str().unwrap()])
+        .args(["--", "sh", "-c", "printf '%s|%s' \"$DB_URL\" \"$MAPPED_URL\""])
+        .output()
+        .expect("failed to run ownkey run");
+    assert!(output.status.success(), "run should succeed");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "postgres://secret|postgres://secret"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn run_forwards_child_exit_status() {
+    let path = temp_vault_path("exit_status");
+    let path_str = path.to_str().unwrap();
+    init_with_entry(path_str, "k", "v");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["run", "--path", path_str, "--password", "testpw", "-e", "K=k"])
+        .args(["--", "sh", "-c", "exit 42"])
+        .output()
+        .expect("failed to run ownkey run");
+    assert_eq!(output.status.code(), Some(42), "child exit status should be forwarded");
+}
+
+#[test]
+fn run_fails_before_spawning_when_key_is_missing() {
+    let path = temp_vault_path("missing");
+    let path_str = path.to_str().unwrap();
+    init_with_entry(path_str, "k", "v");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["run", "--path", path_str, "--password", "testpw", "-e", "X=nope"])
+        .args(["--", "sh", "-c", "echo should-not-run"])
+        .output()
+        .expect("failed to run ownkey run");
+    assert_eq!(output.status.code(), Some(3), "missing key should exit with 3");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("should-not-run"));
+}
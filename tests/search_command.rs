@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_search_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn search(vault: &str, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(["search", "--path", vault, "--password", "testpw"])
+        .args(args)
+        .output()
+        .expect("failed to run ownkey search")
+}
+
+fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn seeded_vault(test_name: &str) -> PathBuf {
+    let vault = temp_vault_path(test_name);
+    let v = vault.to_str().unwrap();
+    let ownkey = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ownkey"))
+            .args(args)
+            .status()
+            .expect("failed to run ownkey")
+    };
+    assert!(ownkey(&["init", v, "--password", "testpw"]).success());
+    for (k, val) in [("stripe_key", "sk_live_abc"), ("disk", "hunter2"), ("api", "stripe")] {
+        assert!(ownkey(&["add", "--path", v, "--password", "testpw", k, val]).success());
+    }
+    vault
+}
+
+#[test]
+fn search_is_keys_only_unless_values_requested() {
+    let vault = seeded_vault("values");
+    let v = vault.to_str().unwrap();
+
+    let keys_only = search(v, &["hunter"]);
+    assert!(keys_only.status.success());
+    assert_eq!(stdout(&keys_only), "", "values must not be searched by default");
+
+    let with_values = search(v, &["--values", "stripe"]);
+    assert_eq!(stdout(&with_values), "api\tvalue\t100\nstripe_key\tkey\t60\n");
+
+    let _ = fs::remove_file(&vault);
+}
+
+#[test]
+fn exact_regex_and_fuzzy_modes() {
+    let vault = seeded_vault("modes");
+    let v = vault.to_str().unwrap();
+
+    assert_eq!(stdout(&search(v, &["--exact", "disk"])), "disk\tkey\t100\n");
+    assert_eq!(stdout(&search(v, &["--exact", "dis"])), "");
+
+    assert_eq!(stdout(&search(v, &["--regex", "^(api|disk)$"])), "api\tkey\t100\ndisk\tkey\t100\n");
+    let invalid = search(v, &["--regex", "("]);
+    assert_eq!(invalid.status.code(), Some(2), "invalid regex is a usage error");
+
+    let fuzzy = stdout(&search(v, &["--fuzzy", "sk"]));
+    let ranked: Vec<&str> = fuzzy.lines().map(|l| l.split('\t').next().unwrap()).collect();
+    assert_eq!(ranked, vec!["stripe_key", "disk"], "boundary matches rank first");
+
+    let conflict = search(v, &["--exact", "--fuzzy", "sk"]);
+    assert_eq!(conflict.status.code(), Some(2));
+
+    let _ = fs::remove_file(&vault);
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_tag_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn seeded_vault(test_name: &str) -> PathBuf {
+    let vault = temp_vault_path(test_name);
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+    for args in [
+        &["stripe_key", "sk_live", "--tag", "payments", "--tag", "prod"][..],
+        &["stripe_test", "sk_test", "--tag", "payments"][..],
+        &["db_url", "postgres://prod", "--tag", "prod"][..],
+    ] {
+        let mut full = vec!["add", "--path", v, "--password", "testpw"];
+        full.extend_from_slice(args);
+        assert!(ownkey(&full).status.success(), "add {args:?} should succeed");
+    }
+    vault
+}
+
+#[test]
+fn tag_filters_apply_to_list_search_env_and_export() {
+    let vault = seeded_vault("filters");
+    let v = vault.to_str().unwrap();
+    let auth = ["--path", v, "--password", "testpw"];
+
+    let list = ownkey(&[&["list", "--tag", "payments"][..], &auth].concat());
+    assert_eq!(stdout(&list), "stripe_key\nstripe_test\n");
+
+    let both = ownkey(&[&["list", "--tag", "payments", "--tag", "prod"][..], &auth].concat());
+    assert_eq!(stdout(&both), "stripe_key\n", "multiple tags must all match");
+
+    let search = ownkey(&[&["search", "_k", "--tag", "prod"][..], &auth].concat());
+    assert_eq!(stdout(&search), "stripe_key\tkey\t20\n");
+
+    let env = ownkey(&[&["env", "--tag", "prod", "--format", "dotenv"][..], &auth].concat());
+    assert_eq!(stdout(&env), "DB_URL=\"postgres://prod\"\nSTRIPE_KEY=\"sk_live\"\n");
+
+    let export = ownkey(
+        &[&["export", "--format", "json", "--plaintext", "--tag", "payments"][..], &auth].concat(),
+    );
+    let value: serde_json::Value = serde_json::from_slice(&export.stdout).unwrap();
+    let values: Vec<_> = value
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(key, entry)| (key.as_str(), entry["value"].as_str().unwrap()))
+        .collect();
+    assert_eq!(values, [("stripe_key", "sk_live"), ("stripe_test", "sk_test")]);
+
+    let _ = fs::remove_file(&vault);
+}
+
+#[test]
+fn tag_subcommands_edit_and_list_tags() {
+    let vault = seeded_vault("edit");
+    let v = vault.to_str().unwrap();
+    let auth = ["--path", v, "--password", "testpw"];
+
+    assert!(ownkey(&[&["tag", "add", "db_url", "database"][..], &auth].concat()).status.success());
+    assert!(ownkey(&[&["tag", "remove", "db_url", "prod"][..], &auth].concat()).status.success());
+
+    let key_tags = ownkey(&[&["tag", "list", "db_url"][..], &auth].concat());
+    assert_eq!(stdout(&key_tags), "database\n");
+
+    let all = ownkey(&[&["tag", "list"][..], &auth].concat());
+    assert_eq!(stdout(&all), "database\t1\npayments\t2\nprod\t1\n");
+
+    let missing = ownkey(&[&["tag", "add", "nope", "x"][..], &auth].concat());
+    assert_eq!(missing.status.code(), Some(3));
+
+    let bad = ownkey(&[&["tag", "add", "db_url", "has space"][..], &auth].concat());
+    assert_eq!(bad.status.code(), Some(2), "invalid tags are rejected by argument parsing");
+
+    let _ = fs::remove_file(&vault);
+}
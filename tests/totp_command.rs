@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ownkey::totp::Totp;
+use uuid::Uuid;
+
+const SEED: &str = "otpauth://totp/ACME:me?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=SHA256&digits=8";
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_totp_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[test]
+fn totp_prints_current_code_for_stored_seed() {
+    let vault = temp_vault_path("code");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+    let added = ownkey(&["add", "--path", v, "--password", "testpw", "acme_2fa", SEED, "--kind", "totp"]);
+    assert!(added.status.success(), "adding a valid seed should succeed");
+
+    let totp = Totp::parse(SEED).unwrap();
+    let before = totp.code_at(now());
+    let output = ownkey(&["totp", "--path", v, "--password", "testpw", "acme_2fa"]);
+    let after = totp.code_at(now());
+    assert!(output.status.success());
+    let code = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(code.len(), 8);
+    assert!(code == before || code == after, "unexpected code {code}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("s remaining"));
+
+    let _ = fs::remove_file(&vault);
+}
+
+#[test]
+fn totp_rejects_invalid_seeds_and_plain_secrets() {
+    let vault = temp_vault_path("invalid");
+    let v = vault.to_str().unwrap();
+    assert!(ownkey(&["init", v, "--password", "testpw"]).status.success());
+
+    let bad = ownkey(&["add", "--path", v, "--password", "testpw", "x", "not-base32!", "--kind", "totp"]);
+    assert_eq!(bad.status.code(), Some(2), "invalid seeds are refused at add time");
+
+    assert!(ownkey(&["add", "--path", v, "--password", "testpw", "plain", "hunter2"]).status.success());
+    let plain = ownkey(&["totp", "--path", v, "--password", "testpw", "plain"]);
+    assert_eq!(plain.status.code(), Some(2));
+
+    let _ = fs::remove_file(&vault);
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use uuid::Uuid;
+
+fn temp_vault_path(test_name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("ownkey_typed_{test_name}_{}.json", Uuid::new_v4()));
+    path
+}
+
+fn ownkey(vault: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .args(["--path", vault, "--password", "testpw", "--no-session"])
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn typed_entries_expose_and_validate_their_fields() {
+    let vault = temp_vault_path("fields");
+    let v = vault.to_str().unwrap();
+    let db = [
+        "add", "db/prod", "--kind", "database", "--field", "host=db.internal", "--field", "user=app",
+        "--field", "port=5432", "hunter2-but-longer",
+    ];
+    assert!(ownkey(v, &db).status.success());
+
+    assert_eq!(stdout(&ownkey(v, &["view", "db/prod", "--field", "host"])), "db.internal");
+    assert_eq!(stdout(&ownkey(v, &["view", "db/prod", "--field", "password"])), "hunter2-but-longer");
+    assert_eq!(stdout(&ownkey(v, &["view", "db/prod"])), "hunter2-but-longer");
+    assert_eq!(ownkey(v, &["view", "db/prod", "--field", "username"]).status.code(), Some(2));
+    assert_eq!(ownkey(v, &["view", "db/prod", "--field", "name"]).status.code(), Some(2));
+
+    let json: serde_json::Value = serde_json::from_str(&stdout(&ownkey(v, &["view", "db/prod", "--json"]))).unwrap();
+    assert_eq!(json["kind"], "database");
+    assert_eq!(json["fields"]["port"], "5432");
+    assert_eq!(json["fields"]["password"], "hunter2-but-longer");
+
+    let bad_port = ownkey(v, &["update", "db/prod", "--field", "port=http", "--yes"]);
+    assert_eq!(bad_port.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&bad_port.stderr).contains("port"));
+    assert!(ownkey(v, &["update", "db/prod", "--field", "port=", "--yes"]).status.success());
+    assert_eq!(ownkey(v, &["view", "db/prod", "--field", "port"]).status.code(), Some(2));
+
+    let missing_user = ownkey(v, &["add", "site", "--kind", "login", "pw"]);
+    assert_eq!(missing_user.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&missing_user.stderr).contains("username"));
+    let both = ownkey(v, &["add", "site", "--kind", "login", "--field", "password=a", "b"]);
+    assert_eq!(both.status.code(), Some(2));
+    let login = ["add", "site", "--kind", "login", "--field", "username=me", "--field", "password=pw1"];
+    assert!(ownkey(v, &login).status.success());
+    assert_eq!(stdout(&ownkey(v, &["view", "site", "--field", "username"])), "me");
+
+    assert_eq!(ownkey(v, &["add", "deploy", "--kind", "ssh_key", "not a key"]).status.code(), Some(2));
+
+    // Overwriting with another kind drops the old kind's fields.
+    let relogin = [
+        "add", "db/prod", "--force", "--kind", "login", "--field", "username=admin", "pw2",
+    ];
+    let replaced = ownkey(v, &relogin);
+    assert!(replaced.status.success(), "{}", String::from_utf8_lossy(&replaced.stderr));
+    let json: serde_json::Value = serde_json::from_str(&stdout(&ownkey(v, &["view", "db/prod", "--json"]))).unwrap();
+    assert_eq!(json["kind"], "login");
+    assert_eq!(json["fields"], serde_json::json!({"password": "pw2", "username": "admin"}));
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use uuid::Uuid;
+
+fn temp_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ownkey_vaults_{test_name}_{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Run ownkey with its own home directory, so config.toml is private to the test.
+fn ownkey(home: &Path, cwd: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ownkey"))
+        .args(args)
+        .env("HOME", home)
+        .current_dir(cwd)
+        .output()
+        .expect("failed to run ownkey")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn named_vaults_are_picked_by_flag_default_and_marker() {
+    let home = temp_dir("home");
+    let project = temp_dir("project");
+    let nested = project.join("src");
+    fs::create_dir_all(&nested).unwrap();
+    let auth = ["--password", "testpw", "--no-session"];
+
+    let created = ownkey(&home, &project, &[&["vault", "create", "work"][..], &auth].concat());
+    assert!(created.status.success(), "{}", String::from_utf8_lossy(&created.stderr));
+    assert!(home.join(".ownkey/vaults/work.json").exists());
+    let personal = project.join("personal.json");
+    let p = personal.to_str().unwrap();
+    let created = ownkey(&home, &project, &[&["vault", "create", "personal", "--path", p][..], &auth].concat());
+    assert!(created.status.success());
+    assert_eq!(ownkey(&home, &project, &["vault", "create", "work"]).status.code(), Some(2));
+
+    let config = fs::read_to_string(home.join(".ownkey/config.toml")).unwrap();
+    assert!(config.contains("# ownkey configuration"), "comments are kept");
+    assert!(config.contains("[vaults.work]") && config.contains("[vaults.personal]"));
+
+    assert!(ownkey(&home, &project, &[&["-v", "work", "add", "api_key", "w0rk"][..], &auth].concat())
+        .status
+        .success());
+    let list = |cwd: &Path, extra: &[&str]| stdout(&ownkey(&home, cwd, &[extra, &["list"], &auth].concat()));
+    assert!(list(&project, &["-v", "work"]).contains("api_key"));
+    assert!(!list(&project, &["--vault", "personal"]).contains("api_key"));
+    assert_eq!(ownkey(&home, &project, &[&["-v", "nope", "list"][..], &auth].concat()).status.code(), Some(2));
+
+    assert!(ownkey(&home, &project, &["vault", "use", "work"]).status.success());
+    assert!(list(&project, &[]).contains("api_key"));
+    let vaults: serde_json::Value =
+        serde_json::from_str(&stdout(&ownkey(&home, &project, &["vault", "list", "--json"]))).unwrap();
+    let work = vaults.as_array().unwrap().iter().find(|v| v["name"] == "work").unwrap();
+    assert_eq!((work["default"].as_bool(), work["in_use"].as_bool()), (Some(true), Some(true)));
+
+    fs::write(project.join(".ownkey"), "# vault for this project\npersonal\n").unwrap();
+    assert!(!list(&nested, &[]).contains("api_key"));
+    assert!(stdout(&ownkey(&home, &nested, &["vault", "list"])).contains("(in use: "));
+}